@@ -0,0 +1,21 @@
+use crate::r#impl::storage::Storage;
+
+pub fn execute(level: i32) -> Result<(), Box<dyn std::error::Error>> {
+    let mut total_before = 0;
+    let mut total_after = 0;
+
+    let blob_paths = Storage::blob_paths()?;
+    for blob_path in &blob_paths {
+        let (before, after) = Storage::recompress_blob(blob_path, level)?;
+        total_before += before;
+        total_after += after;
+    }
+
+    println!(
+        "Compacted {} blobs: {} KiB -> {} KiB",
+        blob_paths.len(),
+        total_before / 1024,
+        total_after / 1024
+    );
+    Ok(())
+}
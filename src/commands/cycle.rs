@@ -0,0 +1,31 @@
+use crate::commands::pick::restore_types;
+use crate::r#impl::config::Config;
+use crate::r#impl::cycle::Cursor;
+use crate::r#impl::storage::Storage;
+
+/// Put the next older entry on the clipboard, or the next newer one with
+/// `forward`, wrapping around at either end like a kill ring
+pub fn execute(forward: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = Storage::from_file(Config::load()?.retention)?;
+    let entries = storage.get_entries();
+    if entries.is_empty() {
+        return Err("Clipboard history is empty".into());
+    }
+
+    // The newest entry is on the clipboard when a cycle starts. Entries are
+    // ordered newest first, so a deleted one is replaced by the next older.
+    let current = Cursor::load()?
+        .and_then(|cursor| entries.iter().position(|entry| entry.id <= cursor.id))
+        .unwrap_or(0);
+    let next = if forward {
+        (current + entries.len() - 1) % entries.len()
+    } else {
+        (current + 1) % entries.len()
+    };
+
+    let entry = &entries[next];
+    let types = entry.load_types()?;
+    // Saved first, as setting the clipboard may block until it is pasted
+    Cursor::new(entry.id, &types).save()?;
+    restore_types(entry, types)
+}
@@ -0,0 +1,44 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::crypto;
+use crate::r#impl::cycle::Cursor;
+use crate::r#impl::registers::Registers;
+use crate::r#impl::snippets::Snippets;
+use crate::r#impl::storage::Storage;
+use std::fs;
+
+pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+    if !crypto::is_encrypted()? {
+        return Err("Clipboard history is not encrypted".into());
+    }
+
+    let storage = Storage::from_file_as_is(Config::load()?.retention)?;
+    let sealed_blobs = Storage::blob_paths()?;
+    let snippets = Snippets::from_file()?;
+    let registers = Registers::from_file()?;
+    let blobs = storage.load_blobs()?;
+
+    crypto::set_key(None);
+
+    // Rewrite every referenced blob under its content hash
+    for (hash, content) in &blobs {
+        Storage::write_blob(hash, content)?;
+    }
+    storage.to_file()?;
+    snippets.to_file()?;
+    registers.to_file()?;
+    storage.rebuild_index()?;
+    // Sealed with the old key
+    Cursor::reset()?;
+    crypto::remove_params()?;
+
+    // Files are told apart by name, as plain content may look sealed
+    for blob_path in sealed_blobs {
+        let name = blob_path.file_name().and_then(|name| name.to_str());
+        if !name.is_some_and(|name| blobs.contains_key(name)) {
+            fs::remove_file(blob_path)?;
+        }
+    }
+
+    println!("Decrypted {} entries", storage.get_entries().len());
+    Ok(())
+}
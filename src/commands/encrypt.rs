@@ -0,0 +1,53 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::cycle::Cursor;
+use crate::r#impl::registers::Registers;
+use crate::r#impl::snippets::Snippets;
+use crate::r#impl::storage::Storage;
+use crate::r#impl::{crypto, path};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn execute(keyfile: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    if crypto::is_encrypted()? {
+        return Err("Clipboard history is already encrypted".into());
+    }
+
+    let storage = Storage::from_file_as_is(Config::load()?.retention)?;
+    let plain_blobs = Storage::blob_paths()?;
+    let snippets = Snippets::from_file()?;
+    let registers = Registers::from_file()?;
+    let blobs = storage.load_blobs()?;
+
+    let key = crypto::create_key(keyfile)?;
+    crypto::set_key(Some(key));
+
+    // Rewrite every referenced blob under its keyed name
+    for (hash, content) in &blobs {
+        Storage::write_blob(hash, content)?;
+    }
+    storage.to_file()?;
+    snippets.to_file()?;
+    registers.to_file()?;
+    storage.rebuild_index()?;
+    // Sealed with the old key
+    Cursor::reset()?;
+
+    // Files are told apart by name, as plain content may look sealed
+    let sealed_names = blobs
+        .keys()
+        .map(|hash| crypto::blob_name(hash))
+        .collect::<Result<HashSet<_>, _>>()?;
+    for blob_path in plain_blobs {
+        let name = blob_path.file_name().and_then(|name| name.to_str());
+        if !name.is_some_and(|name| sealed_names.contains(name)) {
+            fs::remove_file(blob_path)?;
+        }
+    }
+
+    // Thumbnails are plain copies of images
+    fs::remove_dir_all(path::get_thumbnails_dir()?)?;
+
+    println!("Encrypted {} entries", storage.get_entries().len());
+    Ok(())
+}
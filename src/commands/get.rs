@@ -0,0 +1,56 @@
+use crate::commands::pick::{join_text, select_entries, JoinArgs};
+use crate::commands::preview::preferred_type;
+use crate::r#impl::config::Config;
+use crate::r#impl::convert;
+use crate::r#impl::launcher::{parse_id_range, parse_row_id, parse_row_key};
+use crate::r#impl::snippets::Snippets;
+use crate::r#impl::storage::Storage;
+use std::io::Write;
+
+/// Write an entry's content to stdout, either its most readable type or
+/// `as_type`, converting the entry's image if it does not have that type.
+/// Several entries or a range are written as their text joined together.
+pub fn execute(
+    rows: &[String],
+    as_type: Option<String>,
+    join: &JoinArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keys: Vec<&str> = rows.iter().map(|row| parse_row_key(row)).collect();
+    let storage = Storage::from_file(Config::load()?.retention)?;
+
+    let row = match keys[..] {
+        [key] if parse_id_range(key).is_none() => key,
+        _ => {
+            if as_type.is_some() {
+                return Err("--as only works with a single entry".into());
+            }
+            let snippets = Snippets::from_file()?;
+            let entries = select_entries(&keys, &storage, &snippets, join)?;
+            std::io::stdout().write_all(join_text(&entries, join)?.as_bytes())?;
+            return Ok(());
+        }
+    };
+    let id = parse_row_id(row)?;
+    let entry = storage
+        .get_entry_by_id(id)
+        .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+
+    let content = match as_type {
+        Some(mime_type) if !entry.types.contains_key(&mime_type) => {
+            let (source, _) = entry.get_image_type().ok_or_else(|| {
+                format!("Entry {} has no {} and no image to convert", id, mime_type)
+            })?;
+            convert::convert_image(&entry.types[source].content.load()?, &mime_type)?
+        }
+        Some(mime_type) => entry.types[&mime_type].content.load()?,
+        None => {
+            let mime_type = preferred_type(entry)
+                .or_else(|| entry.types.keys().min().cloned())
+                .ok_or_else(|| format!("Entry {} has no content", id))?;
+            entry.types[&mime_type].content.load()?
+        }
+    };
+
+    std::io::stdout().write_all(&content)?;
+    Ok(())
+}
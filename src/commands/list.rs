@@ -1,19 +1,152 @@
-use crate::r#impl::storage::Storage;
+use crate::r#impl::config::{parse_size, Config};
+use crate::r#impl::mime::MimeClass;
+use crate::r#impl::query::Query;
+use crate::r#impl::snippets::SNIPPET_PREFIX;
+use crate::r#impl::storage::{ClipboardEntry, Storage};
+use crate::r#impl::thumbnail;
+use std::time::SystemTime;
 
-pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
-    let storage = Storage::from_file(100)?;
-    let entries = storage.get_entries();
+// Longest line shown per entry in menus, in characters
+const LINE_LENGTH: usize = 256;
 
-    for entry in entries.iter() {
-        let display_content = if let Some(text_content) = entry.get_text_content() {
-            text_content.clone()
-        } else {
-            entry.get_binary_info()
-        };
+#[derive(clap::Args)]
+pub struct FilterArgs {
+    /// Only entries of this kind (repeatable)
+    #[arg(long, value_enum)]
+    class: Vec<MimeClass>,
+    /// Only entries newer than a duration ago ("2h") or a time ("2024-05-01 09:00:00", UTC)
+    #[arg(long)]
+    since: Option<String>,
+    /// Only entries older than a duration ago or a time
+    #[arg(long)]
+    before: Option<String>,
+    /// Only pinned entries
+    #[arg(long, conflicts_with = "unpinned")]
+    pinned: bool,
+    /// Only entries that are not pinned
+    #[arg(long)]
+    unpinned: bool,
+    /// Minimum total size, e.g. "10KB"
+    #[arg(long)]
+    min_size: Option<String>,
+    /// Maximum total size
+    #[arg(long)]
+    max_size: Option<String>,
+    /// Only entries with this tag (repeatable, all must match)
+    #[arg(long)]
+    tag: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct OutputArgs {
+    /// Print one row per line with thumbnails of image entries, for a launcher
+    #[arg(long, value_enum)]
+    thumbnails: Option<ThumbnailStyle>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum ThumbnailStyle {
+    /// rofi's icon row option, for `rofi -dmenu -show-icons`
+    Rofi,
+    /// wofi's image prefix, for `wofi --dmenu --allow-images`
+    Wofi,
+}
+
+impl FilterArgs {
+    pub fn to_query(&self) -> Result<Query, Box<dyn std::error::Error>> {
+        Ok(Query {
+            text: None,
+            tags: self.tag.clone(),
+            classes: self.class.clone(),
+            since: self.since.as_deref().map(parse_time).transpose()?,
+            before: self.before.as_deref().map(parse_time).transpose()?,
+            pinned: match (self.pinned, self.unpinned) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            min_size: self.min_size.as_deref().map(parse_size).transpose()?,
+            max_size: self.max_size.as_deref().map(parse_size).transpose()?,
+        })
+    }
+}
 
-        // TODO: Configure separator from command line argument
-        print!("{}|{}:::", entry.id, display_content);
+/// Parse either a duration before now or an absolute UTC time
+fn parse_time(s: &str) -> Result<SystemTime, Box<dyn std::error::Error>> {
+    if let Ok(duration) = humantime::parse_duration(s) {
+        return SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| format!("Duration too long: '{}'", s).into());
     }
+    humantime::parse_rfc3339_weak(s)
+        .or_else(|_| humantime::parse_rfc3339_weak(&format!("{} 00:00:00", s)))
+        .map_err(|_| format!("Invalid time: '{}'", s).into())
+}
 
+pub fn execute(filter: FilterArgs, output: OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = Storage::from_file(Config::load()?.retention)?;
+    print_entries(storage.search(&filter.to_query()?), &output);
     Ok(())
 }
+
+pub fn print_entries(entries: Vec<&ClipboardEntry>, output: &OutputArgs) {
+    for entry in entries {
+        let Some(style) = output.thumbnails else {
+            // TODO: Configure separator from command line argument
+            print!("{}|{}:::", entry.id, display_content(entry));
+            continue;
+        };
+
+        // Rows are still listed if a thumbnail cannot be made
+        let thumbnail = thumbnail::path_for(entry).ok().flatten();
+        let row = format!("{}|{}", entry.id, display_line(entry));
+        match (style, thumbnail) {
+            (ThumbnailStyle::Rofi, Some(path)) => {
+                println!("{}\0icon\x1f{}", row, path.display())
+            }
+            (ThumbnailStyle::Wofi, Some(path)) => println!("img:{}:text:{}", path.display(), row),
+            (_, None) => println!("{}", row),
+        }
+    }
+}
+
+/// Text shown for an entry in listings and pickers, after its tags and note
+pub fn display_content(entry: &ClipboardEntry) -> String {
+    let mut annotations: String = entry.tags.iter().map(|tag| format!("#{} ", tag)).collect();
+    if let Some(note) = &entry.note {
+        annotations.push_str(&format!("[{}] ", note));
+    }
+    annotations + &display_text(entry)
+}
+
+fn display_text(entry: &ClipboardEntry) -> String {
+    if entry.is_sensitive() {
+        format!("[[ sensitive: {} ]]", entry.sensitive.join(", "))
+    } else if let Some(text_preview) = entry.get_text_preview() {
+        text_preview
+    } else {
+        entry.get_binary_info()
+    }
+}
+
+/// Menu line of a snippet, "@name" followed by its content
+pub fn snippet_line(name: &str, snippet: &ClipboardEntry) -> String {
+    format!("{}{} {}", SNIPPET_PREFIX, name, display_line(snippet))
+}
+
+/// Single-line, shortened `display_content` for menus, with line breaks made visible
+pub fn display_line(entry: &ClipboardEntry) -> String {
+    single_line(&display_content(entry))
+}
+
+/// Text shortened to one line for menus
+pub fn single_line(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' => '↵',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .take(LINE_LENGTH)
+        .collect()
+}
@@ -0,0 +1,429 @@
+use crate::commands::list::{display_content, display_line, snippet_line};
+use crate::commands::pick::{restore_entry, restore_plain_text, restore_snippet};
+use crate::r#impl::config::{Config, HooksConfig};
+use crate::r#impl::hooks::{self, HookEvent};
+use crate::r#impl::launcher::{self, Row};
+use crate::r#impl::snippets::{self, Snippets, SNIPPET_PREFIX};
+use crate::r#impl::storage::{format_size, ClipboardEntry, ContentRef, Storage};
+use crate::r#impl::thumbnail;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::thread::JoinHandle;
+
+/// What a menu row stands for
+#[derive(Clone)]
+enum Item {
+    Entry(u64),
+    Snippet(String),
+}
+
+enum Outcome {
+    Pick(Item),
+    PickPlainText(Item),
+}
+
+struct Match {
+    item: Item,
+    label: String,
+    positions: Vec<usize>, // matched character positions in the label
+}
+
+struct Menu {
+    storage: Storage,
+    snippets: Snippets,
+    matcher: SkimMatcherV2,
+    query: String,
+    matches: Vec<Match>,
+    state: ListState,
+    status: Option<String>,
+    hooks: HooksConfig,
+    hook_threads: Vec<JoinHandle<()>>,
+}
+
+/// Name of the built-in terminal menu for `--launcher`
+pub const TUI: &str = "tui";
+
+pub fn execute(launcher: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    let storage = Storage::from_file(config.retention)?;
+    let snippets = Snippets::from_file()?;
+
+    if let Some(name) = launcher
+        .or(config.menu.launcher.clone())
+        .filter(|name| name != TUI)
+    {
+        let launcher = launcher::resolve(&name, &config.menu)?;
+        let with_thumbnails = launcher.row.contains("{thumbnail}");
+        let thumbnail = |entry: &ClipboardEntry| {
+            // Rows are still shown if a thumbnail cannot be made
+            with_thumbnails
+                .then(|| thumbnail::path_for(entry).ok().flatten())
+                .flatten()
+        };
+        let rows: Vec<Row> = snippets
+            .iter()
+            .map(|(name, snippet)| Row {
+                key: snippets::row_key(name),
+                label: snippet_line(name, snippet),
+                thumbnail: thumbnail(snippet),
+            })
+            .chain(storage.get_entries().iter().map(|entry| Row {
+                key: entry.id.to_string(),
+                label: display_line(entry),
+                thumbnail: thumbnail(entry),
+            }))
+            .collect();
+        let Some(key) = launcher::run(&launcher, &rows)? else {
+            return Ok(());
+        };
+        let unknown = || format!("Unknown selection '{}'", key);
+        return match key.strip_prefix(SNIPPET_PREFIX) {
+            Some(name) => restore_snippet(snippets.get(name).ok_or_else(unknown)?, false),
+            None => restore_entry(
+                key.parse()
+                    .ok()
+                    .and_then(|id| storage.get_entry_by_id(id))
+                    .ok_or_else(unknown)?,
+            ),
+        };
+    }
+
+    let mut menu = Menu::new(storage, snippets, config.hooks);
+
+    let mut terminal = ratatui::init();
+    let outcome = menu.run(&mut terminal);
+    ratatui::restore();
+
+    for hook in menu.hook_threads.drain(..) {
+        let _ = hook.join();
+    }
+
+    // Setting the clipboard may block until it is pasted, so the UI is closed first
+    match outcome? {
+        Some(Outcome::Pick(item @ Item::Snippet(_))) => restore_snippet(menu.entry(&item)?, false),
+        Some(Outcome::PickPlainText(item @ Item::Snippet(_))) => {
+            restore_snippet(menu.entry(&item)?, true)
+        }
+        Some(Outcome::Pick(item)) => restore_entry(menu.entry(&item)?),
+        Some(Outcome::PickPlainText(item)) => restore_plain_text(menu.entry(&item)?),
+        None => Ok(()),
+    }
+}
+
+impl Menu {
+    fn new(storage: Storage, snippets: Snippets, hooks: HooksConfig) -> Self {
+        let mut menu = Self {
+            storage,
+            snippets,
+            matcher: SkimMatcherV2::default(),
+            query: String::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+            status: None,
+            hooks,
+            hook_threads: Vec::new(),
+        };
+        menu.update_matches();
+        menu
+    }
+
+    fn entry(&self, item: &Item) -> Result<&ClipboardEntry, Box<dyn std::error::Error>> {
+        match item {
+            Item::Entry(id) => self
+                .storage
+                .get_entry_by_id(*id)
+                .ok_or_else(|| format!("Entry with ID {} not found", id).into()),
+            Item::Snippet(name) => self
+                .snippets
+                .get(name)
+                .ok_or_else(|| format!("Snippet '{}' not found", name).into()),
+        }
+    }
+
+    fn selected(&self) -> Option<Item> {
+        self.state
+            .selected()
+            .and_then(|index| self.matches.get(index))
+            .map(|m| m.item.clone())
+    }
+
+    /// Selected history entry, or a status message if a snippet is selected
+    fn selected_entry(&mut self) -> Option<u64> {
+        match self.selected()? {
+            Item::Entry(id) => Some(id),
+            Item::Snippet(_) => {
+                self.status = Some("Snippets are managed with `clippers snippet`".to_string());
+                None
+            }
+        }
+    }
+
+    fn update_matches(&mut self) {
+        let snippets = self
+            .snippets
+            .iter()
+            .map(|(name, snippet)| (Item::Snippet(name.clone()), snippet_line(name, snippet)));
+        let entries = self
+            .storage
+            .get_entries()
+            .iter()
+            .map(|entry| (Item::Entry(entry.id), display_line(entry)));
+
+        let mut scored: Vec<(i64, Match)> = snippets
+            .chain(entries)
+            .filter_map(|(item, label)| {
+                let (score, positions) = if self.query.is_empty() {
+                    (0, Vec::new())
+                } else {
+                    self.matcher.fuzzy_indices(&label, &self.query)?
+                };
+                Some((
+                    score,
+                    Match {
+                        item,
+                        label,
+                        positions,
+                    },
+                ))
+            })
+            .collect();
+
+        // Stable sort keeps snippets, then newer entries first among equal scores
+        scored.sort_by_key(|(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, m)| m).collect();
+
+        let selected = self.state.selected().unwrap_or(0);
+        self.state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(selected.min(self.matches.len() - 1))
+        });
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<Option<Outcome>, Box<dyn std::error::Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(outcome) = self.handle_key(key)? {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Returns `Some` when the menu should close
+    fn handle_key(
+        &mut self,
+        key: KeyEvent,
+    ) -> Result<Option<Option<Outcome>>, Box<dyn std::error::Error>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        self.status = None;
+
+        match key.code {
+            KeyCode::Esc => return Ok(Some(None)),
+            KeyCode::Char('c') if ctrl => return Ok(Some(None)),
+            KeyCode::Enter => return Ok(self.selected().map(|item| Some(Outcome::Pick(item)))),
+            KeyCode::Char('y') if ctrl => {
+                return Ok(self
+                    .selected()
+                    .map(|item| Some(Outcome::PickPlainText(item))));
+            }
+            KeyCode::Char('d') if ctrl => {
+                if let Some(id) = self.selected_entry() {
+                    let entry = self.entry(&Item::Entry(id))?;
+                    let hook =
+                        hooks::spawn(&self.hooks, HookEvent::Delete, entry, display_line(entry));
+                    self.hook_threads.extend(hook);
+                    self.storage.delete_entry(id)?;
+                    self.status = Some(format!("Deleted entry {}", id));
+                    self.update_matches();
+                }
+            }
+            KeyCode::Char('t') if ctrl => {
+                if let Some(id) = self.selected_entry() {
+                    let pinned = !self.entry(&Item::Entry(id))?.pinned;
+                    self.storage.set_pinned(id, pinned)?;
+                    let action = if pinned { "Pinned" } else { "Unpinned" };
+                    self.status = Some(format!("{} entry {}", action, id));
+                    self.update_matches();
+                }
+            }
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Char('p') if ctrl => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            KeyCode::Char('n') if ctrl => self.state.select_next(),
+            KeyCode::PageUp => self.state.scroll_up_by(10),
+            KeyCode::PageDown => self.state.scroll_down_by(10),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.update_matches();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.state.select_first();
+                self.update_matches();
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [input_area, main_area, footer_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(main_area);
+
+        let title = format!(
+            " clippers {}/{} ",
+            self.matches.len(),
+            self.storage.get_entries().len() + self.snippets.iter().count()
+        );
+        frame.render_widget(
+            Paragraph::new(self.query.as_str()).block(Block::bordered().title(title)),
+            input_area,
+        );
+        frame.set_cursor_position(Position::new(
+            input_area.x + 1 + self.query.chars().count() as u16,
+            input_area.y + 1,
+        ));
+
+        self.draw_list(frame, list_area);
+        self.draw_detail(frame, detail_area);
+
+        let footer = match &self.status {
+            Some(status) => Line::from(status.as_str()).style(Style::new().fg(Color::Yellow)),
+            None => Line::from("Enter pick · C-y plain text · C-t pin · C-d delete · Esc quit")
+                .style(Style::new().add_modifier(Modifier::DIM)),
+        };
+        frame.render_widget(footer, footer_area);
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let highlight = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|m| {
+                let pinned = self.entry(&m.item).is_ok_and(|entry| entry.pinned);
+                // Snippet labels start with their name
+                let id = match &m.item {
+                    Item::Entry(id) => id.to_string(),
+                    Item::Snippet(_) => String::new(),
+                };
+                let mut spans = vec![
+                    Span::raw(if pinned { "* " } else { "  " }),
+                    Span::styled(
+                        format!("{:>4} ", id),
+                        Style::new().add_modifier(Modifier::DIM),
+                    ),
+                ];
+                spans.extend(m.label.chars().enumerate().map(|(i, c)| {
+                    if m.positions.contains(&i) {
+                        Span::styled(c.to_string(), highlight)
+                    } else {
+                        Span::raw(c.to_string())
+                    }
+                }));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::bordered())
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let Some((item, entry)) = self
+            .selected()
+            .and_then(|item| Some((item.clone(), self.entry(&item).ok()?)))
+        else {
+            frame.render_widget(Block::bordered(), area);
+            return;
+        };
+        let title = match item {
+            Item::Entry(id) => format!("Entry {}", id),
+            Item::Snippet(name) => format!("Snippet {}", name),
+        };
+
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled(title, bold),
+                Span::raw(if entry.pinned { "  (pinned)" } else { "" }),
+            ]),
+            Line::from(format!(
+                "{} · {:?} · {}",
+                humantime::format_rfc3339_seconds(entry.timestamp),
+                entry.get_class(),
+                format_size(entry.get_size()),
+            )),
+        ];
+        if !entry.tags.is_empty() {
+            lines.push(Line::from(format!("Tags: {}", entry.tags.join(", "))));
+        }
+        if let Some(note) = &entry.note {
+            lines.push(Line::from(format!("Note: {}", note)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Types", bold));
+
+        let mut mime_types: Vec<&String> = entry.types.keys().collect();
+        mime_types.sort();
+        for mime_type in mime_types {
+            let stored = &entry.types[mime_type];
+            let mut info = format!("  {}  {}", mime_type, format_size(stored.meta.size as u64));
+            if let Some(image) = &stored.meta.image {
+                info.push_str(&format!(
+                    "  {} {}x{}",
+                    image.format, image.width, image.height
+                ));
+            }
+            if matches!(stored.content, ContentRef::External(_)) {
+                info.push_str("  (blob)");
+            }
+            lines.push(Line::from(info));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Content", bold));
+        if entry.is_sensitive() {
+            lines.push(Line::from(display_content(entry)));
+        } else if let Some(preview) = entry.get_text_preview() {
+            lines.extend(preview.lines().map(|line| Line::from(line.to_string())));
+        } else {
+            lines.push(Line::from(entry.get_binary_info()));
+        }
+
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered())
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+}
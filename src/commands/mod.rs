@@ -1,3 +1,20 @@
+pub mod compact;
+pub mod cycle;
+pub mod decrypt;
+pub mod encrypt;
+pub mod get;
 pub mod list;
+pub mod menu;
+pub mod note;
 pub mod pick;
+pub mod pin;
+pub mod preview;
+pub mod queue;
+pub mod register;
+pub mod reindex;
+pub mod rofi;
+pub mod rules;
+pub mod search;
+pub mod snippet;
+pub mod tag;
 pub mod watch;
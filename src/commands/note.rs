@@ -0,0 +1,9 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::storage::Storage;
+
+pub fn execute(id: u64, note: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut storage = Storage::from_file(Config::load()?.retention)?;
+    // An empty note is the same as none
+    storage.set_note(id, note.filter(|note| !note.trim().is_empty()))?;
+    Ok(())
+}
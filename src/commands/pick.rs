@@ -1,25 +1,344 @@
+use crate::commands::list::{display_line, single_line};
+use crate::commands::menu::TUI;
 use crate::r#impl::clipboard::create_clipboard;
-use crate::r#impl::storage::Storage;
-use std::io::{self, Read};
+use crate::r#impl::config::{Config, MenuConfig};
+use crate::r#impl::convert;
+use crate::r#impl::hooks::{self, HookEvent};
+use crate::r#impl::launcher;
+use crate::r#impl::metadata;
+use crate::r#impl::mime;
+use crate::r#impl::snippets::{Snippets, SNIPPET_PREFIX};
+use crate::r#impl::storage::{ClipboardEntry, Storage};
+use crate::r#impl::template;
+use crate::r#impl::transform;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::thread::JoinHandle;
 
-pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+/// How `pick` and `get` join the text of several entries
+#[derive(clap::Args)]
+pub struct JoinArgs {
+    /// Separator between the text of several entries: newline, space, comma, tab,
+    /// or any other text, where \n and \t stand for newline and tab
+    #[arg(long, default_value = "newline")]
+    separator: String,
+    /// Order of several entries
+    #[arg(long, value_enum, default_value = "chosen")]
+    order: JoinOrder,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+enum JoinOrder {
+    /// As given, with ranges in the direction written
+    Chosen,
+    /// Oldest first
+    Chronological,
+}
+
+impl JoinArgs {
+    fn separator(&self) -> String {
+        match self.separator.as_str() {
+            "newline" => "\n".to_string(),
+            "space" => " ".to_string(),
+            "comma" => ",".to_string(),
+            "tab" => "\t".to_string(),
+            custom => custom.replace("\\n", "\n").replace("\\t", "\t"),
+        }
+    }
+}
+
+pub fn execute(
+    ids: &[String],
+    strip_metadata: bool,
+    transforms: &[String],
+    join: &JoinArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let keys = if ids.is_empty() {
+        io::stdin().read_to_string(&mut input)?;
+        launcher::parse_row_keys(&input)
+    } else {
+        ids.iter().map(|id| launcher::parse_row_key(id)).collect()
+    };
+
+    let config = Config::load()?;
+    // Resolved up front, so a typo fails before anything is read
+    let transforms = transforms
+        .iter()
+        .map(|name| transform::resolve(name, &config.transforms))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let key = match keys[..] {
+        // Reported as an invalid ID below
+        [] => launcher::parse_row_key(&input),
+        [key] if launcher::parse_id_range(key).is_none() => key,
+        _ => {
+            if strip_metadata {
+                return Err("--strip-metadata cannot be used with several entries".into());
+            }
+            let storage = Storage::from_file(config.retention)?;
+            let snippets = Snippets::from_file()?;
+            let entries = select_entries(&keys, &storage, &snippets, join)?;
+            let mut text = join_text(&entries, join)?;
+            for transform in &transforms {
+                text = transform.apply(&text)?;
+            }
+            return restore_joined_text(&text);
+        }
+    };
+
+    // Snippets are offered in pickers as "@name"
+    let (storage, snippets);
+    let entry = match key.strip_prefix(SNIPPET_PREFIX) {
+        Some(name) => {
+            snippets = Snippets::from_file()?;
+            snippets
+                .get(name)
+                .ok_or_else(|| format!("Snippet '{}' not found", name))?
+        }
+        None => {
+            let id = launcher::parse_row_id(key)?;
+            storage = Storage::from_file(config.retention)?;
+            storage
+                .get_entry_by_id(id)
+                .ok_or_else(|| format!("Entry with ID {} not found", id))?
+        }
+    };
+
+    let expanded = if key.starts_with(SNIPPET_PREFIX) {
+        expand_snippet(entry, &config.menu)?
+    } else {
+        None
+    };
+    if !transforms.is_empty() {
+        let mut text = expanded
+            .or_else(|| entry.get_text_content())
+            .ok_or_else(|| format!("'{}' has no text to transform", key))?;
+        for transform in &transforms {
+            text = transform.apply(&text)?;
+        }
+        return restore_text(entry, &text);
+    }
+    if let Some(text) = expanded {
+        return restore_text(entry, &text);
+    }
+
+    let mut types = entry.load_types()?;
+    if strip_metadata {
+        metadata::strip_types(&mut types);
+    }
+    restore_types(entry, types)
+}
+
+/// Put all representations of an entry back on the clipboard, with images
+/// also offered in other formats if enabled
+pub fn restore_entry(entry: &ClipboardEntry) -> Result<(), Box<dyn std::error::Error>> {
+    restore_types(entry, entry.load_types()?)
+}
+
+/// Entries for row keys, IDs and ranges of IDs, in the requested order.
+/// IDs missing from a range are skipped, as the range may span deleted entries.
+pub fn select_entries<'a>(
+    keys: &[&str],
+    storage: &'a Storage,
+    snippets: &'a Snippets,
+    join: &JoinArgs,
+) -> Result<Vec<&'a ClipboardEntry>, Box<dyn std::error::Error>> {
+    let mut entries = Vec::new();
+    for key in keys {
+        if let Some(name) = key.strip_prefix(SNIPPET_PREFIX) {
+            let snippet = snippets
+                .get(name)
+                .ok_or_else(|| format!("Snippet '{}' not found", name))?;
+            entries.push(snippet);
+        } else if let Some((first, last)) = launcher::parse_id_range(key) {
+            let ids = first.min(last)..=first.max(last);
+            let mut in_range: Vec<_> = storage
+                .get_entries()
+                .iter()
+                .filter(|entry| ids.contains(&entry.id))
+                .collect();
+            in_range.sort_by_key(|entry| entry.id);
+            if first > last {
+                in_range.reverse();
+            }
+            entries.extend(in_range);
+        } else {
+            let id = launcher::parse_row_id(key)?;
+            let entry = storage
+                .get_entry_by_id(id)
+                .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+            entries.push(entry);
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(format!("No entries found for {}", keys.join(" ")).into());
+    }
+    if join.order == JoinOrder::Chronological {
+        entries.sort_by_key(|entry| entry.timestamp);
+    }
+    Ok(entries)
+}
 
-    // Extract ID, ignoring parts after '|'
-    let id_str = input.trim().split('|').next().unwrap_or("").trim();
+/// Text of several entries joined by the requested separator
+pub fn join_text(
+    entries: &[&ClipboardEntry],
+    join: &JoinArgs,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let texts = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .get_text_content()
+                .ok_or_else(|| format!("Entry {} has no text to join", entry.id))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(texts.join(&join.separator()))
+}
+
+/// Put text joined from several entries on the clipboard as a new entry
+fn restore_joined_text(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let types: HashMap<String, Vec<u8>> = mime::offered_text_types()
+        .iter()
+        .map(|mime_type| (mime_type.to_string(), text.as_bytes().to_vec()))
+        .collect();
+    // Not in the history yet, so the pick hook sees it without an ID
+    let hook = hooks::spawn_for_text(
+        &Config::load()?.hooks,
+        HookEvent::Pick,
+        text,
+        single_line(text),
+    );
+    serve(hook, &types, &HashMap::new())
+}
+
+/// Put a snippet on the clipboard, as plain text with its placeholders
+/// filled in if it has any
+pub fn restore_snippet(
+    snippet: &ClipboardEntry,
+    plain_text: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match expand_snippet(snippet, &Config::load()?.menu)? {
+        Some(text) => restore_text(snippet, &text),
+        None if plain_text => restore_plain_text(snippet),
+        None => restore_entry(snippet),
+    }
+}
 
-    let id: u64 = id_str
-        .parse()
-        .map_err(|_| format!("Invalid ID: '{}'", id_str))?;
+/// Text of a snippet with its placeholders filled in, `None` if it has none
+fn expand_snippet(
+    snippet: &ClipboardEntry,
+    menu: &MenuConfig,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let Some(text) = snippet
+        .get_text_content()
+        .filter(|text| template::has_placeholders(text))
+    else {
+        return Ok(None);
+    };
+    let expanded = template::expand(&text, |label| ask(label, menu))?;
+    Ok(Some(expanded))
+}
+
+/// Ask for the answer to a `{{prompt:LABEL}}` placeholder with the configured
+/// launcher, or on the terminal if there is none. Not on stdin, which holds
+/// the chosen row when piped from `clippers list`.
+fn ask(label: &str, menu: &MenuConfig) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(name) = menu.launcher.as_deref().filter(|name| *name != TUI) {
+        let launcher = launcher::resolve(name, menu)?;
+        return launcher::prompt(&launcher, label)?
+            .ok_or_else(|| format!("No answer given for '{}'", label).into());
+    }
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|_| {
+            format!(
+                "No terminal to ask for '{}', set a launcher in [menu] to answer prompts",
+                label
+            )
+        })?;
+    write!(tty, "{}: ", label)?;
+    tty.flush()?;
+    let mut answer = String::new();
+    if BufReader::new(tty).read_line(&mut answer)? == 0 {
+        return Err(format!("No answer given for '{}'", label).into());
+    }
+    Ok(answer.trim_end_matches(['\n', '\r']).to_string())
+}
 
-    let storage = Storage::from_file(100)?;
-    let entry = storage
-        .get_entry_by_id(id)
-        .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+/// Put the given content of an entry's types on the clipboard
+pub fn restore_types(
+    entry: &ClipboardEntry,
+    types: HashMap<String, Vec<u8>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conversions = if Config::load()?.images.convert {
+        convert::image_conversions(entry)
+    } else {
+        HashMap::new()
+    };
 
-    let mut clipboard = create_clipboard()?;
-    clipboard.set_types(&entry.types)?;
+    set_clipboard(entry, &types, &conversions)
+}
+
+/// Put text made from an entry on the clipboard as plain text only
+fn restore_text(entry: &ClipboardEntry, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let types = mime::offered_text_types()
+        .iter()
+        .map(|mime_type| (mime_type.to_string(), text.as_bytes().to_vec()))
+        .collect();
+    set_clipboard(entry, &types, &HashMap::new())
+}
+
+/// Set the clipboard to content of an entry, running the pick hook meanwhile
+fn set_clipboard(
+    entry: &ClipboardEntry,
+    types: &HashMap<String, Vec<u8>>,
+    conversions: &HashMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hook = hooks::spawn(
+        &Config::load()?.hooks,
+        HookEvent::Pick,
+        entry,
+        display_line(entry),
+    );
+    serve(hook, types, conversions)
+}
+
+/// Set the clipboard, then wait for the pick hook started for its content
+fn serve(
+    hook: Option<JoinHandle<()>>,
+    types: &HashMap<String, Vec<u8>>,
+    conversions: &HashMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = create_clipboard()
+        .and_then(|mut clipboard| clipboard.set_types_converted(types, conversions));
+
+    if let Some(hook) = hook {
+        let _ = hook.join();
+    }
+    result
+}
 
-    Ok(())
+/// Put only the plain text representations of an entry on the clipboard,
+/// dropping rich formats such as HTML
+pub fn restore_plain_text(entry: &ClipboardEntry) -> Result<(), Box<dyn std::error::Error>> {
+    let types = entry
+        .types
+        .iter()
+        .filter(|(mime_type, _)| mime::is_plain_text(mime_type))
+        .map(|(mime_type, stored)| Ok((mime_type.clone(), stored.content.load()?)))
+        .collect::<Result<HashMap<_, _>, Box<dyn std::error::Error>>>()?;
+    if types.is_empty() {
+        // Text converted from HTML or RTF
+        let text = entry
+            .get_text_content()
+            .ok_or_else(|| format!("Entry {} has no plain text", entry.id))?;
+        return restore_text(entry, &text);
+    }
+    set_clipboard(entry, &types, &HashMap::new())
 }
@@ -0,0 +1,217 @@
+use crate::commands::list::display_content;
+use crate::r#impl::config::Config;
+use crate::r#impl::graphics::{self, Protocol};
+use crate::r#impl::launcher::parse_row_id;
+use crate::r#impl::mime::{self, MimeClass};
+use crate::r#impl::storage::{format_size, ClipboardEntry, Storage};
+use std::io::Write;
+use unicode_width::UnicodeWidthChar;
+
+const TAB_WIDTH: usize = 4;
+
+// Elements that never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+pub struct PreviewArgs {
+    pub mime_type: Option<String>,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    pub protocol: Option<Protocol>,
+}
+
+/// Print an entry for a preview pane. `row` is an ID or a row printed by `list`.
+pub fn execute(row: &str, args: PreviewArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let id = parse_row_id(row)?;
+    let storage = Storage::from_file(Config::load()?.retention)?;
+    let entry = storage
+        .get_entry_by_id(id)
+        .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+
+    let (width, height) = preview_size(&args);
+    let mut out = std::io::stdout().lock();
+
+    if entry.is_sensitive() {
+        writeln!(out, "{}", display_content(entry))?;
+        return Ok(());
+    }
+
+    let Some(mime_type) = args.mime_type.clone().or_else(|| preferred_type(entry)) else {
+        writeln!(out, "{}", entry.get_binary_info())?;
+        return Ok(());
+    };
+    let stored = entry
+        .types
+        .get(&mime_type)
+        .ok_or_else(|| format!("Entry {} has no type {}", id, mime_type))?;
+    let content = stored.content.load()?;
+
+    if let Some(image) = &stored.meta.image {
+        writeln!(
+            out,
+            "{} {}x{} {}",
+            image.format,
+            image.width,
+            image.height,
+            format_size(stored.meta.size as u64)
+        )?;
+        let protocol = args.protocol.unwrap_or_else(Protocol::detect);
+        // Undecodable formats still get their description
+        let _ = graphics::render(
+            &mut out,
+            &content,
+            protocol,
+            width,
+            height.saturating_sub(1),
+        );
+        return Ok(());
+    }
+
+    match String::from_utf8(content) {
+        Ok(text) => {
+            for line in wrap(&format_text(&mime_type, &text), width as usize) {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        Err(_) => writeln!(out, "{}", entry.get_binary_info())?,
+    }
+    Ok(())
+}
+
+/// Box to fit the preview in: the given size, the fzf preview window, or the terminal
+fn preview_size(args: &PreviewArgs) -> (u16, u16) {
+    let env_size = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+    let terminal = ratatui::crossterm::terminal::size().unwrap_or((80, 24));
+    (
+        args.width
+            .or_else(|| env_size("FZF_PREVIEW_COLUMNS"))
+            .unwrap_or(terminal.0),
+        args.height
+            .or_else(|| env_size("FZF_PREVIEW_LINES"))
+            .unwrap_or(terminal.1),
+    )
+}
+
+/// Most useful type to show: the image for images, else plain text, else markup
+pub fn preferred_type(entry: &ClipboardEntry) -> Option<String> {
+    if entry.get_class() == MimeClass::Image {
+        if let Some((mime_type, _)) = entry.get_image_type() {
+            return Some(mime_type.clone());
+        }
+    }
+
+    let mut mime_types: Vec<&String> = entry.types.keys().collect();
+    mime_types.sort();
+    mime_types
+        .iter()
+        .find(|mime_type| mime::is_plain_text(mime_type))
+        .or_else(|| mime_types.iter().find(|mime_type| mime::is_text(mime_type)))
+        .map(|mime_type| mime_type.to_string())
+}
+
+fn format_text(mime_type: &str, text: &str) -> String {
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(text) {
+        if value.is_object() || value.is_array() {
+            if let Ok(pretty) = serde_json::to_string_pretty(&value) {
+                return pretty;
+            }
+        }
+    }
+
+    let trimmed = text.trim();
+    let looks_like_html = trimmed.starts_with('<') && trimmed.ends_with('>') && text.contains("</");
+    if MimeClass::of(mime_type) == MimeClass::Html || looks_like_html {
+        return pretty_html(text).trim_end().to_string();
+    }
+    text.to_string()
+}
+
+/// One tag or text run per line, indented by nesting depth
+fn pretty_html(html: &str) -> String {
+    let mut pretty = String::new();
+    let mut depth: usize = 0;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map_or(rest.len(), |end| end + 1)
+        } else {
+            rest.find('<').unwrap_or(rest.len())
+        };
+        let (token, tail) = rest.split_at(end);
+        rest = tail;
+
+        if !token.starts_with('<') {
+            let text = token.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                push_indented(&mut pretty, depth, &text);
+            }
+            continue;
+        }
+
+        let name = token
+            .trim_start_matches(['<', '/'])
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let closing = token.starts_with("</");
+        if closing {
+            depth = depth.saturating_sub(1);
+        }
+        push_indented(&mut pretty, depth, token);
+
+        let opening = !closing
+            && !token.ends_with("/>")
+            && !token.starts_with("<!")
+            && !token.starts_with("<?")
+            && !VOID_ELEMENTS.contains(&name.as_str());
+        if opening {
+            depth += 1;
+        }
+    }
+    pretty
+}
+
+fn push_indented(pretty: &mut String, depth: usize, line: &str) {
+    pretty.push_str(&"  ".repeat(depth));
+    pretty.push_str(line);
+    pretty.push('\n');
+}
+
+/// Break text into lines of at most `width` columns, with tabs shown as
+/// arrows and other control characters as their Unicode control pictures
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for source_line in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for c in source_line.chars() {
+            let visible = match c {
+                '\t' => {
+                    let pad = TAB_WIDTH - line_width % TAB_WIDTH;
+                    format!("→{}", " ".repeat(pad - 1))
+                }
+                '\u{0}'..='\u{1f}' => char::from_u32(0x2400 + c as u32).unwrap_or('�').to_string(),
+                '\u{7f}' => "␡".to_string(),
+                c if c.is_control() => "�".to_string(),
+                c => c.to_string(),
+            };
+            for c in visible.chars() {
+                let char_width = c.width().unwrap_or(0);
+                if line_width + char_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push(c);
+                line_width += char_width;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
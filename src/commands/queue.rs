@@ -0,0 +1,39 @@
+use crate::commands::list::display_line;
+use crate::r#impl::config::Config;
+use crate::r#impl::queue::{self, Queue};
+use crate::r#impl::storage::Storage;
+
+pub fn start() -> Result<(), Box<dyn std::error::Error>> {
+    if !queue::SUPPORTED {
+        return Err("Paste queues are only supported on Linux".into());
+    }
+    Queue::start()?;
+    println!("Started paste queue, entries copied now are pasted in the same order");
+    Ok(())
+}
+
+pub fn status() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(queue) = Queue::load()? else {
+        println!("No paste queue is active");
+        return Ok(());
+    };
+    println!("Paste queue active, {} entries left", queue.len());
+
+    let storage = Storage::from_file(Config::load()?.retention)?;
+    for id in queue.ids() {
+        match storage.get_entry_by_id(id) {
+            Some(entry) => println!("{}\t{}", id, display_line(entry)),
+            None => println!("{}\t(deleted)", id),
+        }
+    }
+    Ok(())
+}
+
+pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+    let queue = Queue::stop()?;
+    println!(
+        "Stopped paste queue, {} entries were not pasted",
+        queue.len()
+    );
+    Ok(())
+}
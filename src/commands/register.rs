@@ -0,0 +1,63 @@
+use crate::commands::list::display_line;
+use crate::commands::pick::restore_entry;
+use crate::r#impl::clipboard::create_clipboard;
+use crate::r#impl::config::Config;
+use crate::r#impl::metadata;
+use crate::r#impl::mime;
+use crate::r#impl::registers::{self, Registers};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Read};
+
+/// Store piped stdin in a register, or the current clipboard if nothing is piped
+pub fn yank(register: char, mime_type: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    // Checked before the clipboard is read
+    registers::validate_name(register)?;
+    let config = Config::load()?;
+
+    let mut content = Vec::new();
+    if !io::stdin().is_terminal() {
+        io::stdin().read_to_end(&mut content)?;
+    }
+    let mut types = if content.is_empty() {
+        let mut clipboard = create_clipboard()?;
+        let mut types = HashMap::new();
+        for mime_type in clipboard.list_types() {
+            let content = clipboard.get_by_type(&mime_type)?;
+            types.insert(mime_type, content);
+        }
+        if types.is_empty() {
+            return Err("Clipboard is empty".into());
+        }
+        types
+    } else {
+        let mime_type = match mime_type {
+            Some(mime_type) => mime_type,
+            None if std::str::from_utf8(&content).is_ok() => mime::plain_text_type().to_string(),
+            None => return Err("Content is not text, set its MIME type with --type".into()),
+        };
+        HashMap::from([(mime_type, content)])
+    };
+    if config.images.strip_metadata {
+        metadata::strip_types(&mut types);
+    }
+
+    let mut registers = Registers::from_file()?;
+    registers.set(register, types)?;
+    println!("Yanked into register '{}'", register);
+    Ok(())
+}
+
+pub fn put(register: char) -> Result<(), Box<dyn std::error::Error>> {
+    let registers = Registers::from_file()?;
+    let entry = registers
+        .get(register)
+        .ok_or_else(|| format!("Register '{}' is empty", register))?;
+    restore_entry(entry)
+}
+
+pub fn list() -> Result<(), Box<dyn std::error::Error>> {
+    for (name, entry) in Registers::from_file()?.iter() {
+        println!("{}\t{}", name, display_line(entry));
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::storage::Storage;
+
+pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = Storage::from_file(Config::load()?.retention)?;
+    storage.rebuild_index()?;
+    println!("Indexed {} entries", storage.get_entries().len());
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::commands::list::{display_line, snippet_line};
+use crate::r#impl::config::Config;
+use crate::r#impl::hooks::{self, HookEvent};
+use crate::r#impl::launcher::escape_markup;
+use crate::r#impl::mime::MimeClass;
+use crate::r#impl::snippets::{self, Snippets};
+use crate::r#impl::storage::{ClipboardEntry, Storage};
+use crate::r#impl::thumbnail;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Values of ROFI_RETV, kb-custom-N is reported as 9 + N
+const RETV_SELECTED: u32 = 1;
+const RETV_DELETE: u32 = 10; // kb-custom-1
+const RETV_TOGGLE_PIN: u32 = 11; // kb-custom-2
+
+/// Entry point for rofi's script mode, which runs this again after every action
+pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+    let retv: u32 = std::env::var("ROFI_RETV")
+        .ok()
+        .and_then(|retv| retv.parse().ok())
+        .unwrap_or(0);
+    // An entry ID, or "@name" for snippets
+    let info = std::env::var("ROFI_INFO").ok();
+    let id: Option<u64> = info.as_ref().and_then(|info| info.parse().ok());
+
+    let config = Config::load()?;
+    let mut storage = Storage::from_file(config.retention)?;
+    let snippets = Snippets::from_file()?;
+    let keys: Vec<String> = snippets
+        .iter()
+        .map(|(name, _)| snippets::row_key(name))
+        .chain(storage.get_entries().iter().map(|e| e.id.to_string()))
+        .collect();
+    let position = info
+        .as_ref()
+        .and_then(|info| keys.iter().position(|key| key == info));
+
+    let mut stdout = std::io::stdout().lock();
+    match (retv, id) {
+        (RETV_SELECTED, _) if info.is_some() => {
+            // Printing no rows closes rofi, which waits for our output to end,
+            // so the clipboard is served from a separate process
+            let mut child = Command::new(std::env::current_exe()?)
+                .arg("pick")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            child
+                .stdin
+                .take()
+                .ok_or("Failed to open pick stdin")?
+                .write_all(format!("{}\n", info.unwrap_or_default()).as_bytes())?;
+            return Ok(());
+        }
+        (RETV_DELETE, Some(id)) => {
+            let hook = storage.get_entry_by_id(id).and_then(|entry| {
+                hooks::spawn(&config.hooks, HookEvent::Delete, entry, display_line(entry))
+            });
+            storage.delete_entry(id)?;
+            if let Some(hook) = hook {
+                let _ = hook.join();
+            }
+        }
+        (RETV_TOGGLE_PIN, Some(id)) => {
+            let pinned = storage.get_entry_by_id(id).is_some_and(|e| e.pinned);
+            storage.set_pinned(id, !pinned)?;
+        }
+        _ => {}
+    }
+
+    writeln!(stdout, "\0prompt\x1fclippers")?;
+    writeln!(
+        stdout,
+        "\0message\x1f{}",
+        escape_markup("kb-custom-1: delete · kb-custom-2: pin/unpin")
+    )?;
+    writeln!(stdout, "\0markup-rows\x1ftrue")?;
+    writeln!(stdout, "\0no-custom\x1ftrue")?;
+    writeln!(stdout, "\0use-hot-keys\x1ftrue")?;
+    if let Some(position) = position {
+        let last = keys.len().saturating_sub(1);
+        writeln!(stdout, "\0keep-selection\x1ftrue")?;
+        writeln!(stdout, "\0new-selection\x1f{}", position.min(last))?;
+    }
+
+    for (name, snippet) in snippets.iter() {
+        let key = snippets::row_key(name);
+        writeln!(
+            stdout,
+            "{}\0info\x1f{}\x1fmeta\x1f{}\x1ficon\x1f{}",
+            escape_markup(&snippet_line(name, snippet)),
+            key,
+            key,
+            icon(snippet)
+        )?;
+    }
+    for entry in storage.get_entries() {
+        let marker = if entry.pinned { "<b>*</b> " } else { "" };
+        writeln!(
+            stdout,
+            "{}{}\0info\x1f{}\x1fmeta\x1f{}\x1ficon\x1f{}",
+            marker,
+            escape_markup(&display_line(entry)),
+            entry.id,
+            entry.id,
+            icon(entry)
+        )?;
+    }
+    Ok(())
+}
+
+/// Thumbnail for images, otherwise an icon name from the icon theme
+fn icon(entry: &ClipboardEntry) -> String {
+    // A missing thumbnail should not hide the entry
+    if let Ok(Some(thumbnail_path)) = thumbnail::path_for(entry) {
+        return thumbnail_path.display().to_string();
+    }
+    match entry.get_class() {
+        MimeClass::Text => "text-x-generic",
+        MimeClass::Html => "text-html",
+        MimeClass::Image => "image-x-generic",
+        MimeClass::Files => "folder",
+        MimeClass::Other => "application-octet-stream",
+    }
+    .to_string()
+}
@@ -0,0 +1,51 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::mime;
+use crate::r#impl::rules::RuleSet;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Show what the capture rules would do with content read from stdin,
+/// without storing anything
+pub fn test(mime_type: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    if config.rules.is_empty() {
+        return Err("No rules configured, add [[rules]] to the config".into());
+    }
+    let rules = RuleSet::from_config(&config.rules)?;
+
+    let mut content = Vec::new();
+    io::stdin().read_to_end(&mut content)?;
+    let mime_type = mime_type.unwrap_or_else(|| mime::plain_text_type().to_string());
+    let mut types = HashMap::from([(mime_type.clone(), content)]);
+
+    let outcome = rules.apply(&mut types);
+    if outcome.matched.is_empty() {
+        println!("No rules matched");
+        return Ok(());
+    }
+
+    println!("Matched: {}", outcome.matched.join(", "));
+    if let Some(rule) = &outcome.ignored_by {
+        println!("Ignored by: {}", rule);
+        return Ok(());
+    }
+    if !outcome.tags.is_empty() {
+        println!("Tags: {}", outcome.tags.join(", "));
+    }
+    if outcome.pin {
+        println!("Pinned");
+    }
+    if !outcome.sensitive.is_empty() {
+        println!("Sensitive: {}", outcome.sensitive.join(", "));
+    }
+    if let Some(ttl) = outcome.expire_after {
+        println!("Expires after: {}", humantime::format_duration(ttl));
+    }
+    if outcome.rewritten {
+        println!("Rewritten content:");
+        let mut stdout = io::stdout();
+        stdout.write_all(&types[&mime_type])?;
+        stdout.write_all(b"\n")?;
+    }
+    Ok(())
+}
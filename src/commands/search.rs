@@ -0,0 +1,33 @@
+use crate::commands::list::{print_entries, FilterArgs, OutputArgs};
+use crate::r#impl::config::Config;
+use crate::r#impl::query::Query;
+use crate::r#impl::storage::Storage;
+
+pub struct SearchMode {
+    pub regex: bool,
+    pub substring: bool,
+    pub case_sensitive: bool,
+}
+
+pub fn execute(
+    pattern: &str,
+    mode: SearchMode,
+    filter: FilterArgs,
+    output: OutputArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut query = filter.to_query()?;
+    let storage = Storage::from_file(Config::load()?.retention)?;
+
+    if mode.regex || mode.substring {
+        // Scan every entry, newest first
+        query.text = Some(Query::text_pattern(
+            pattern,
+            mode.regex,
+            mode.case_sensitive,
+        )?);
+        print_entries(storage.search(&query), &output);
+    } else {
+        print_entries(storage.search_ranked(pattern, &query)?, &output);
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::commands::list::display_line;
+use crate::commands::pick::restore_snippet;
+use crate::r#impl::config::Config;
+use crate::r#impl::mime;
+use crate::r#impl::snippets::Snippets;
+use crate::r#impl::storage::Storage;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Where the content of a new snippet comes from
+pub enum SnippetSource {
+    Stdin,
+    File(PathBuf),
+    Entry(u64),
+}
+
+pub fn add(
+    name: &str,
+    source: SnippetSource,
+    mime_type: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let types = match source {
+        // All representations of the entry are kept
+        SnippetSource::Entry(id) => {
+            let storage = Storage::from_file(Config::load()?.retention)?;
+            storage
+                .get_entry_by_id(id)
+                .ok_or_else(|| format!("Entry with ID {} not found", id))?
+                .load_types()?
+        }
+        SnippetSource::Stdin | SnippetSource::File(_) => {
+            let content = match source {
+                SnippetSource::File(path) => fs::read(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+                _ => {
+                    let mut content = Vec::new();
+                    io::stdin().read_to_end(&mut content)?;
+                    content
+                }
+            };
+            let mime_type = match mime_type {
+                Some(mime_type) => mime_type,
+                None if std::str::from_utf8(&content).is_ok() => {
+                    mime::plain_text_type().to_string()
+                }
+                None => return Err("Content is not text, set its MIME type with --type".into()),
+            };
+            HashMap::from([(mime_type, content)])
+        }
+    };
+
+    let mut snippets = Snippets::from_file()?;
+    snippets.add(name, types)?;
+    println!("Saved snippet '{}'", name);
+    Ok(())
+}
+
+pub fn list() -> Result<(), Box<dyn std::error::Error>> {
+    for (name, snippet) in Snippets::from_file()?.iter() {
+        println!("{}\t{}", name, display_line(snippet));
+    }
+    Ok(())
+}
+
+pub fn paste(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let snippets = Snippets::from_file()?;
+    let snippet = snippets
+        .get(name)
+        .ok_or_else(|| format!("Snippet '{}' not found", name))?;
+    restore_snippet(snippet, false)
+}
+
+pub fn remove(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut snippets = Snippets::from_file()?;
+    snippets.remove(name)?;
+    println!("Removed snippet '{}'", name);
+    Ok(())
+}
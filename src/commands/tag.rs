@@ -0,0 +1,8 @@
+use crate::r#impl::config::Config;
+use crate::r#impl::storage::Storage;
+
+pub fn execute(id: u64, tags: &[String], remove: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut storage = Storage::from_file(Config::load()?.retention)?;
+    storage.set_tags(id, tags, remove)?;
+    Ok(())
+}
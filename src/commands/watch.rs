@@ -1,14 +1,44 @@
-use crate::r#impl::clipboard::create_clipboard;
-use crate::r#impl::storage::Storage;
+use crate::commands::list::display_line;
+use crate::r#impl::clipboard::{self, create_clipboard, Clipboard};
+use crate::r#impl::config::{Config, HooksConfig, RetentionConfig, SecretAction};
+use crate::r#impl::crypto;
+use crate::r#impl::cycle::Cursor;
+use crate::r#impl::hooks::{self, HookEvent};
+use crate::r#impl::metadata;
+use crate::r#impl::queue::{self, Queue};
+use crate::r#impl::richtext;
+use crate::r#impl::rules::{Outcome, RuleSet};
+use crate::r#impl::secrets::SecretDetector;
+use crate::r#impl::storage::{ClipboardEntry, Storage};
 use std::collections::hash_map::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    let detector = if config.secrets.enabled {
+        Some(SecretDetector::from_config(&config.secrets)?)
+    } else {
+        None
+    };
+    let rules = RuleSet::from_config(&config.rules)?;
+    let sensitive_ttl = config.secrets.expire_after.map(Duration::from_secs);
+    let strip_metadata = config.images.strip_metadata;
+    let add_plain_text = config.text.add_plain_text;
+
     let mut clipboard = create_clipboard()?;
 
-    let mut storage = Storage::from_file(100).unwrap_or_else(|_| {
+    // Unlock up front, so a wrong key is an error rather than a fresh history
+    crypto::current_key()?;
+
+    let retention = config.retention;
+    let storage = Storage::from_file(retention.clone()).unwrap_or_else(|_| {
         println!("No existing clipboard history found, starting fresh.");
-        Storage::new(100)
+        Storage::new(retention.clone())
     });
+    let storage = Arc::new(Mutex::new(storage));
+
+    spawn_retention_thread(Arc::clone(&storage), retention.clone());
 
     println!("Starting clipboard monitor...");
 
@@ -18,12 +48,33 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
         let _ = clipboard.wait();
     }
     {
+        // Queued entry currently on the clipboard
+        let mut offered: Option<u64> = None;
+
         loop {
-            clipboard.wait().map_err(|e| {
+            if queue::SUPPORTED {
+                if let Err(e) = serve_queue(clipboard.as_mut(), &storage, &mut offered) {
+                    eprintln!("Error offering queued entry: {}", e);
+                }
+            }
+
+            let changes = clipboard.wait().map_err(|e| {
                 eprintln!("Error waiting for clipboard change: {}", e);
                 e
             })?;
 
+            if changes.pasted {
+                if let Err(e) = pop_queue(offered.take()) {
+                    eprintln!("Error updating paste queue: {}", e);
+                }
+            }
+            // Something may have been copied in the same dispatch as the paste
+            if !changes.copied {
+                continue;
+            }
+            // Copied content replaces the queued entry
+            offered = None;
+
             let types = clipboard.list_types();
 
             let mut type_content_map = HashMap::new();
@@ -33,13 +84,191 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
                     .expect("Failed to get content");
                 type_content_map.insert(content_type.clone(), content);
             }
+            // Only the image a picked entry was converted from is kept
+            clipboard::remove_converted(&mut type_content_map);
 
-            if !type_content_map.is_empty() {
-                storage.add_entry(type_content_map);
-                println!("Stored clipboard entry with {} types", types.len());
-            } else {
+            if type_content_map.is_empty() {
                 println!("No valid content to store.");
+                continue;
+            }
+
+            // Entries put back by `clippers cycle` are already in the history,
+            // anything else starts the next cycle from the newest entry
+            if let Ok(Some(cursor)) = Cursor::load() {
+                if cursor.set_by_cycle(&type_content_map) {
+                    println!("Clipboard set by cycle, not stored");
+                    continue;
+                }
+                if let Err(e) = Cursor::reset() {
+                    eprintln!("Error resetting cycle: {}", e);
+                }
+            }
+
+            if strip_metadata && metadata::strip_types(&mut type_content_map) {
+                println!("Removed image metadata");
+            }
+
+            let outcome = rules.apply(&mut type_content_map);
+            if let Some(rule) = &outcome.ignored_by {
+                println!("Entry ignored by rule '{}'", rule);
+                continue;
+            }
+            if !outcome.matched.is_empty() {
+                println!("Applied rules: {}", outcome.matched.join(", "));
+            }
+
+            if add_plain_text && richtext::add_plain_text(&mut type_content_map) {
+                println!("Added plain text converted from rich text");
+            }
+
+            let mut storage = storage.lock().map_err(|_| "History lock poisoned")?;
+
+            // Pick up changes made by other commands, e.g. pinning
+            if let Ok(reloaded) = Storage::from_file(retention.clone()) {
+                *storage = reloaded;
+            }
+
+            let detection = detector.as_ref().and_then(|detector| {
+                detector
+                    .scan(&type_content_map)
+                    .map(|detection| (detector, detection))
+            });
+            let Some((detector, detection)) = detection else {
+                let stored = ClipboardEntry::new(type_content_map)
+                    .and_then(|entry| store(&mut storage, entry, &outcome, &config.hooks));
+                match stored {
+                    Ok(()) => println!("Stored clipboard entry with {} types", types.len()),
+                    Err(e) => eprintln!("Error storing clipboard entry: {}", e),
+                }
+                continue;
+            };
+
+            let secret_rules = detection.rules.join(", ");
+            match detection.action {
+                SecretAction::Drop => {
+                    println!(
+                        "Secret detected (rules: {}), entry not stored",
+                        secret_rules
+                    );
+                }
+                SecretAction::Redact => {
+                    detector.redact(&mut type_content_map);
+                    let stored = ClipboardEntry::new(type_content_map)
+                        .and_then(|entry| store(&mut storage, entry, &outcome, &config.hooks));
+                    match stored {
+                        Ok(()) => println!(
+                            "Secret detected (rules: {}), stored redacted entry",
+                            secret_rules
+                        ),
+                        Err(e) => eprintln!("Error storing redacted entry: {}", e),
+                    }
+                }
+                SecretAction::Sensitive => {
+                    let stored = ClipboardEntry::new(type_content_map).and_then(|mut entry| {
+                        entry.mark_sensitive(detection.rules, sensitive_ttl);
+                        store(&mut storage, entry, &outcome, &config.hooks)
+                    });
+                    match stored {
+                        Ok(()) => println!(
+                            "Secret detected (rules: {}), stored as sensitive",
+                            secret_rules
+                        ),
+                        Err(e) => eprintln!("Error storing sensitive entry: {}", e),
+                    }
+                }
             }
         }
     }
 }
+
+/// Apply the actions of matched rules, add the entry to history and start the capture hook
+fn store(
+    storage: &mut Storage,
+    mut entry: ClipboardEntry,
+    outcome: &Outcome,
+    hooks: &HooksConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    outcome.apply_to(&mut entry);
+    let id = storage.push_entry(entry)?;
+
+    if let Some(mut queue) = Queue::load()? {
+        queue.push(id);
+        queue.save()?;
+        println!("Added to paste queue, {} entries queued", queue.len());
+    }
+
+    // Not joined, so a slow hook never holds up the next capture
+    if let Some(entry) = storage.get_entry_by_id(id) {
+        hooks::spawn(hooks, HookEvent::Capture, entry, display_line(entry));
+    }
+    Ok(())
+}
+
+/// Keep the first entry of an active paste queue on the clipboard
+fn serve_queue(
+    clipboard: &mut dyn Clipboard,
+    storage: &Mutex<Storage>,
+    offered: &mut Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut queue) = Queue::load()? else {
+        return Ok(());
+    };
+    let storage = storage.lock().map_err(|_| "History lock poisoned")?;
+
+    while let Some(id) = queue.front() {
+        let Some(entry) = storage.get_entry_by_id(id) else {
+            // Deleted while queued
+            queue.pop();
+            queue.save()?;
+            continue;
+        };
+        if *offered != Some(id) {
+            clipboard.offer(&entry.load_types()?)?;
+            *offered = Some(id);
+            println!("Offering queued entry {}", id);
+        }
+        break;
+    }
+    Ok(())
+}
+
+/// Drop a queued entry once it was pasted, so the next one is offered
+fn pop_queue(pasted: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut queue) = Queue::load()? else {
+        return Ok(());
+    };
+    if pasted.is_some() && queue.front() == pasted {
+        queue.pop();
+        queue.save()?;
+        println!("Pasted queued entry, {} entries left", queue.len());
+    }
+    Ok(())
+}
+
+/// Enforce retention limits between captures, e.g. for entries that expire,
+/// and delete blobs of entries that are gone
+fn spawn_retention_thread(storage: Arc<Mutex<Storage>>, retention: RetentionConfig) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(retention.check_interval);
+
+        let Ok(mut storage) = storage.lock() else {
+            return;
+        };
+        let Ok(mut current) = Storage::from_file_as_is(retention.clone()) else {
+            continue;
+        };
+
+        if current.enforce_retention() {
+            match current.to_file() {
+                Ok(()) => println!("Removed entries past retention limits"),
+                Err(e) => eprintln!("Error enforcing retention limits: {}", e),
+            }
+        }
+        match current.remove_unreferenced_blobs() {
+            Ok(0) => {}
+            Ok(removed) => println!("Removed {} unreferenced blobs", removed),
+            Err(e) => eprintln!("Error removing unreferenced blobs: {}", e),
+        }
+        *storage = current;
+    });
+}
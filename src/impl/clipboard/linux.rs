@@ -1,6 +1,6 @@
 #![cfg(target_os = "linux")]
 
-use super::Clipboard;
+use super::{Changes, Clipboard, CONVERTED_MARKER};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use wayland_client::{
@@ -16,6 +16,12 @@ use wayland_protocols_wlr::data_control::v1::client::{
     zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
 };
 
+// Offered along with queued content, so the watcher does not capture it again
+const QUEUE_MARKER: &str = "application/x-clippers-queue";
+
+/// User data of sources offering queued content, with the content by type
+struct QueueSource(HashMap<String, Vec<u8>>);
+
 struct AppState {
     seat: Option<WlSeat>,
     data_control_manager: Option<ZwlrDataControlManagerV1>,
@@ -31,6 +37,12 @@ struct AppState {
 
     // For setting clipboard, needed because we need to pass data to callback
     types_to_set: HashMap<String, Vec<u8>>,
+    // Offered types converted on request: target type -> (source type, source content)
+    types_to_convert: HashMap<String, (String, Vec<u8>)>,
+
+    // Source of queued content, served on every request until replaced
+    offered_source: Option<ZwlrDataControlSourceV1>,
+    offered_pasted: bool,
 }
 
 delegate_noop!(AppState: ignore WlSeat);
@@ -96,6 +108,15 @@ impl Dispatch<ZwlrDataControlDeviceV1, (), AppState> for AppState {
             DataControlDeviceEvent::Selection { id } => {
                 // TODO: Handle null case later
                 let offer = id.unwrap();
+                let is_queued = state
+                    .offer_mime_types
+                    .get(&offer.id())
+                    .is_some_and(|mime_types| mime_types.iter().any(|m| m == QUEUE_MARKER));
+                if is_queued {
+                    state.offer_mime_types.remove(&offer.id());
+                    offer.destroy();
+                    return;
+                }
                 state.current_selection = Some(offer);
                 state.got_selection = true;
             }
@@ -135,14 +156,11 @@ impl Dispatch<ZwlrDataControlOfferV1, ()> for AppState {
         _qhandle: &wayland_client::QueueHandle<Self>,
     ) {
         use wayland_protocols_wlr::data_control::v1::client::zwlr_data_control_offer_v1::Event;
-        match event {
-            Event::Offer { mime_type } => {
-                let offer_id = proxy.id();
-                if let Some(mime_types) = state.offer_mime_types.get_mut(&offer_id) {
-                    mime_types.push(mime_type.clone());
-                }
+        if let Event::Offer { mime_type } = event {
+            let offer_id = proxy.id();
+            if let Some(mime_types) = state.offer_mime_types.get_mut(&offer_id) {
+                mime_types.push(mime_type.clone());
             }
-            _ => {}
         }
     }
 }
@@ -159,16 +177,71 @@ impl Dispatch<ZwlrDataControlSourceV1, (), AppState> for AppState {
         use std::io::Write;
         use wayland_protocols_wlr::data_control::v1::client::zwlr_data_control_source_v1::Event;
 
+        if let Event::Send { mime_type, fd } = event {
+            let mut file: std::fs::File = fd.into();
+            if mime_type == CONVERTED_MARKER {
+                let converted: Vec<&str> =
+                    state.types_to_convert.keys().map(String::as_str).collect();
+                file.write_all(converted.join("\n").as_bytes())
+                    .expect("Failed to write to clipboard fd");
+                return;
+            }
+            if let Some((source, source_content)) = state.types_to_convert.get(&mime_type) {
+                // Decoded and re-encoded only now, as most pastes never ask for it
+                match crate::r#impl::convert::convert_image(source_content, &mime_type) {
+                    Ok(content) => file
+                        .write_all(&content)
+                        .expect("Failed to write to clipboard fd"),
+                    Err(e) => eprintln!("Failed to convert image to {}: {}", mime_type, e),
+                }
+                // Served in another format, which counts for the source type
+                state.types_to_set.remove(source);
+                return;
+            }
+            let content = match state.types_to_set.get(&mime_type) {
+                Some(c) => c.clone(),
+                None => return, // seems like compositor (?) may request a type twice
+            };
+            file.write_all(&content)
+                .expect("Failed to write to clipboard fd");
+            state.types_to_set.remove(&mime_type);
+        }
+    }
+}
+
+impl Dispatch<ZwlrDataControlSourceV1, QueueSource, AppState> for AppState {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrDataControlSourceV1,
+        event: <ZwlrDataControlSourceV1 as wayland_client::Proxy>::Event,
+        data: &QueueSource,
+        _conn: &Connection,
+        _qhandle: &wayland_client::QueueHandle<Self>,
+    ) {
+        use std::io::Write;
+        use wayland_protocols_wlr::data_control::v1::client::zwlr_data_control_source_v1::Event;
+
         match event {
             Event::Send { mime_type, fd } => {
                 let mut file: std::fs::File = fd.into();
-                let content = match state.types_to_set.get(&mime_type) {
-                    Some(c) => c.clone(),
-                    None => return, // seems like compositor (?) may request a type twice
+                let Some(content) = data.0.get(&mime_type) else {
+                    return;
                 };
-                file.write_all(&content)
-                    .expect("Failed to write to clipboard fd");
-                state.types_to_set.remove(&mime_type);
+                // The watcher keeps running, so a failed paste is not fatal
+                if let Err(e) = file.write_all(content) {
+                    eprintln!("Error serving queued content: {}", e);
+                    return;
+                }
+                // Requests still queued for a replaced source do not count
+                if state.offered_source.as_ref() == Some(proxy) {
+                    state.offered_pasted = true;
+                }
+            }
+            Event::Cancelled => {
+                if state.offered_source.as_ref() == Some(proxy) {
+                    state.offered_source = None;
+                }
+                proxy.destroy();
             }
             _ => {}
         }
@@ -203,6 +276,9 @@ impl LinuxClipboard {
             got_selection: false,
             current_selection: None,
             types_to_set: HashMap::new(),
+            types_to_convert: HashMap::new(),
+            offered_source: None,
+            offered_pasted: false,
         };
         event_queue.blocking_dispatch(&mut state)?;
 
@@ -275,23 +351,65 @@ impl Clipboard for LinuxClipboard {
         Vec::new()
     }
 
-    fn wait(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn wait(&mut self) -> Result<Changes, Box<dyn std::error::Error>> {
         self.state.got_selection = false;
 
         loop {
+            // A paste may already have been served while reading the selection
+            if self.state.got_selection || self.state.offered_pasted {
+                return Ok(Changes {
+                    copied: std::mem::take(&mut self.state.got_selection),
+                    pasted: std::mem::take(&mut self.state.offered_pasted),
+                });
+            }
+
             self.event_queue
                 .blocking_dispatch(&mut self.state)
                 .map_err(|e| format!("Error waiting for clipboard events: {}", e))?;
-
-            if self.state.got_selection {
-                return Ok(());
-            }
         }
     }
 
     fn set_types(
         &mut self,
         types: &HashMap<String, Vec<u8>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_types_converted(types, &HashMap::new())
+    }
+
+    fn offer(
+        &mut self,
+        types: &HashMap<String, Vec<u8>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let manager = self
+            .state
+            .data_control_manager
+            .as_ref()
+            .ok_or("No data control manager available")?;
+        let device = self
+            .state
+            .data_control_device
+            .as_ref()
+            .ok_or("No data control device available")?;
+        let source =
+            manager.create_data_source(&self.event_queue.handle(), QueueSource(types.clone()));
+
+        for content_type in types.keys() {
+            source.offer(content_type.to_string());
+        }
+        source.offer(QUEUE_MARKER.to_string());
+        device.set_selection(Some(&source));
+
+        // The previous source is cancelled by the compositor
+        self.state.offered_source = Some(source);
+        self.state.offered_pasted = false;
+        self.conn.roundtrip()?;
+        Ok(())
+    }
+
+    fn set_types_converted(
+        &mut self,
+        types: &HashMap<String, Vec<u8>>,
+        conversions: &HashMap<String, String>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let manager = self
             .state
@@ -306,10 +424,20 @@ impl Clipboard for LinuxClipboard {
         let source = manager.create_data_source(&self.event_queue.handle(), ());
 
         self.state.types_to_set = types.clone();
-
-        for (content_type, _content) in types.iter() {
+        self.state.types_to_convert = conversions
+            .iter()
+            .filter_map(|(target, source)| {
+                let content = types.get(source)?.clone();
+                Some((target.clone(), (source.clone(), content)))
+            })
+            .collect();
+
+        for content_type in types.keys().chain(self.state.types_to_convert.keys()) {
             source.offer(content_type.to_string());
         }
+        if !self.state.types_to_convert.is_empty() {
+            source.offer(CONVERTED_MARKER.to_string());
+        }
         device.set_selection(Some(&source));
 
         self.conn.roundtrip()?;
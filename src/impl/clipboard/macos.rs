@@ -1,7 +1,7 @@
 #![cfg(target_os = "macos")]
 #![allow(unexpected_cfgs)] // To suppress warnings when using msg_send!
 
-use super::Clipboard;
+use super::{Changes, Clipboard};
 use cocoa::base::{id, nil};
 use cocoa::foundation::NSString;
 use objc::runtime::{Class, Object};
@@ -94,7 +94,7 @@ impl Clipboard for NSPasteboard {
         }
     }
 
-    fn wait(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn wait(&mut self) -> Result<Changes, Box<dyn std::error::Error>> {
         let initial_change_count = self.get_change_count();
 
         loop {
@@ -102,7 +102,10 @@ impl Clipboard for NSPasteboard {
             let current_change_count = self.get_change_count();
 
             if current_change_count != initial_change_count {
-                return Ok(());
+                return Ok(Changes {
+                    copied: true,
+                    pasted: false,
+                });
             }
         }
     }
@@ -1,19 +1,82 @@
+/// What happened while `wait` was blocked, both may happen at once
+#[derive(Default)]
+pub struct Changes {
+    /// Other content was copied
+    pub copied: bool,
+    /// Content from `offer` was pasted
+    pub pasted: bool,
+}
+
+/// Offered along with image types converted by clippers, listing them one per
+/// line, so the watcher only stores the type they were converted from
+pub const CONVERTED_MARKER: &str = "application/x-clippers-converted";
+
+/// Drop the types clippers converted when offering content, and the marker
+/// listing them. Returns whether there were any.
+pub fn remove_converted(types: &mut std::collections::HashMap<String, Vec<u8>>) -> bool {
+    let Some(converted) = types.remove(CONVERTED_MARKER) else {
+        return false;
+    };
+    for mime_type in String::from_utf8_lossy(&converted).lines() {
+        types.remove(mime_type);
+    }
+    true
+}
+
 /// Common interface for clipboard operations across different platforms
 pub trait Clipboard: std::panic::RefUnwindSafe {
     fn get_by_type(&mut self, content_type: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
 
-    #[allow(dead_code)]
     fn get_string(&mut self) -> Option<String>;
 
     fn list_types(&self) -> Vec<String>;
 
     /// Wait for the next clipboard change
-    fn wait(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    fn wait(&mut self) -> Result<Changes, Box<dyn std::error::Error>>;
 
     fn set_types(
         &mut self,
         types: &std::collections::HashMap<String, Vec<u8>>,
     ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Offer content without waiting for it to be pasted. It is served on
+    /// every request while `wait` runs, until other content is copied, and
+    /// `wait` also returns when it is pasted. Used for the paste queue.
+    fn offer(
+        &mut self,
+        _types: &std::collections::HashMap<String, Vec<u8>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Err("Offering content while watching is not supported on this platform".into())
+    }
+
+    /// Like `set_types`, also offering image types converted from a stored
+    /// one (target type -> source type). Converts everything up front unless
+    /// the platform can produce content on request.
+    fn set_types_converted(
+        &mut self,
+        types: &std::collections::HashMap<String, Vec<u8>>,
+        conversions: &std::collections::HashMap<String, String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut all_types = types.clone();
+        let mut converted_types = Vec::new();
+        for (target, source) in conversions {
+            // Formats that fail to convert are simply not offered
+            if let Some(Ok(converted)) = types
+                .get(source)
+                .map(|content| crate::r#impl::convert::convert_image(content, target))
+            {
+                all_types.insert(target.clone(), converted);
+                converted_types.push(target.as_str());
+            }
+        }
+        if !converted_types.is_empty() {
+            all_types.insert(
+                CONVERTED_MARKER.to_string(),
+                converted_types.join("\n").into_bytes(),
+            );
+        }
+        self.set_types(&all_types)
+    }
 }
 
 pub fn create_clipboard() -> Result<Box<dyn Clipboard>, Box<dyn std::error::Error>> {
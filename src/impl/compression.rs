@@ -0,0 +1,55 @@
+use std::io::Read;
+
+// Blob layout: MAGIC | format tag | content, raw or as a zstd frame
+const MAGIC: &[u8] = b"CLPRSBLB";
+const TAG_RAW: u8 = 0;
+const TAG_ZSTD: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+// Blobs written before the format tag: LEGACY_MAGIC | zstd frame, or raw
+// content with no header at all
+const LEGACY_MAGIC: &[u8] = b"CLPRSZST";
+
+const COMPRESS_THRESHOLD: usize = 4 * 1024; // 4KB
+pub const DEFAULT_LEVEL: i32 = 3;
+
+/// Compress content above the threshold. Content that does not shrink
+/// (PNG, JPEG, ...) is stored raw, so reading it back stays cheap.
+pub fn compress(content: &[u8], level: i32) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if content.len() > COMPRESS_THRESHOLD {
+        let compressed = zstd::encode_all(content, level)?;
+        if compressed.len() < content.len() {
+            return Ok(with_header(TAG_ZSTD, &compressed));
+        }
+    }
+    Ok(with_header(TAG_RAW, content))
+}
+
+fn with_header(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_LEN + body.len());
+    data.extend_from_slice(MAGIC);
+    data.push(tag);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Decompress data written by `compress`, or by earlier versions
+pub fn decompress(data: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if data.len() >= HEADER_LEN && data.starts_with(MAGIC) {
+        return match data[MAGIC.len()] {
+            TAG_RAW => Ok(data[HEADER_LEN..].to_vec()),
+            TAG_ZSTD => decode(&data[HEADER_LEN..]),
+            tag => Err(format!("Unknown blob format {}", tag).into()),
+        };
+    }
+    if data.starts_with(LEGACY_MAGIC) {
+        return decode(&data[LEGACY_MAGIC.len()..]);
+    }
+    Ok(data)
+}
+
+fn decode(frame: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut content = Vec::new();
+    zstd::Decoder::new(frame)?.read_to_end(&mut content)?;
+    Ok(content)
+}
@@ -0,0 +1,352 @@
+use crate::r#impl::mime::MimeClass;
+use crate::r#impl::path;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// User configuration, read from `config.toml`. Every field has a default so
+/// a missing file or a partial file is fine.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub secrets: SecretsConfig,
+    pub encryption: EncryptionConfig,
+    pub retention: RetentionConfig,
+    pub menu: MenuConfig,
+    pub images: ImagesConfig,
+    pub text: TextConfig,
+    /// User-defined `pick --transform` names, each a shell command that reads
+    /// the text on stdin and prints the result
+    pub transforms: HashMap<String, String>,
+    /// Rules applied to new entries before they are stored, in order
+    pub rules: Vec<RuleConfig>,
+    pub hooks: HooksConfig,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = path::get_config_file_path()?;
+        if !file_path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&file_path)?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid config {}: {}", file_path.display(), e).into())
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretAction {
+    /// Store the entry, but hide its preview and let it expire
+    Sensitive,
+    /// Replace the matched text before storing
+    Redact,
+    /// Do not store the entry at all
+    Drop,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    pub enabled: bool,
+    /// Action for rules that do not specify their own
+    pub action: SecretAction,
+    /// Seconds before a sensitive entry is removed from history, kept until
+    /// removed like any other entry if unset
+    pub expire_after: Option<u64>,
+    /// Flag long random-looking tokens even if no pattern matches. Off by
+    /// default, as hashes, IDs and file names look random too.
+    pub entropy: bool,
+    /// Shannon entropy in bits per character
+    pub entropy_threshold: f64,
+    pub entropy_min_length: usize,
+    /// Names of built-in rules to turn off
+    pub disabled_rules: Vec<String>,
+    /// Additional user-defined rules
+    pub rules: Vec<SecretRuleConfig>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: SecretAction::Sensitive,
+            expire_after: None,
+            entropy: false,
+            entropy_threshold: 4.0,
+            entropy_min_length: 24,
+            disabled_rules: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SecretRuleConfig {
+    pub name: String,
+    pub pattern: String,
+    pub action: Option<SecretAction>,
+}
+
+/// Where to get the key for an encrypted store. Without either option, the
+/// passphrase is taken from `CLIPPERS_PASSPHRASE` or prompted for.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    pub keyfile: Option<PathBuf>,
+    /// Shell command printing the passphrase, e.g. `pass show clippers`
+    pub passphrase_command: Option<String>,
+}
+
+/// Limits on what is kept in history. Pinned entries are never removed
+/// and do not count towards the limits.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub max_entries: usize,
+    /// Drop entries older than this, e.g. "7d"
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub max_age: Option<Duration>,
+    /// Total size of externalized content to keep, e.g. "500MB"
+    #[serde(deserialize_with = "deserialize_optional_size")]
+    pub max_blob_size: Option<u64>,
+    /// Maximum number of entries per kind of content, e.g. `image = 10`
+    pub max_per_class: HashMap<MimeClass, usize>,
+    /// How often `watch` enforces the limits between captures
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub check_interval: Duration,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 100,
+            max_age: None,
+            max_blob_size: None,
+            max_per_class: HashMap::new(),
+            check_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct MenuConfig {
+    /// Launcher used by `clippers menu` without `--launcher`, e.g. "rofi".
+    /// The built-in terminal menu if unset.
+    pub launcher: Option<String>,
+    /// Custom launchers by name, also overriding built-in ones
+    pub launchers: HashMap<String, LauncherConfig>,
+}
+
+/// How to run an external picker. `command` is run with `sh -c`, after
+/// replacing `{prompt}` and `{lines}` (number of rows, at most 20).
+#[derive(Deserialize, Clone)]
+pub struct LauncherConfig {
+    pub command: String,
+    #[serde(default)]
+    pub separator: LauncherSeparator,
+    /// Escape rows for Pango markup
+    #[serde(default)]
+    pub markup: bool,
+    #[serde(default)]
+    pub output: LauncherOutput,
+    /// Format of each row, from `{id}`, `{label}` and `{thumbnail}` (path of
+    /// an image entry's thumbnail, empty for other entries)
+    #[serde(default = "default_launcher_row")]
+    pub row: String,
+    /// Command asking for a line of text, e.g. for snippet prompts. `command`
+    /// is used with no rows if unset and `output` is "text".
+    pub prompt: Option<String>,
+}
+
+pub const DEFAULT_LAUNCHER_ROW: &str = "{id}|{label}";
+
+fn default_launcher_row() -> String {
+    DEFAULT_LAUNCHER_ROW.to_string()
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LauncherSeparator {
+    #[default]
+    Newline,
+    Nul,
+}
+
+/// What the launcher prints for the chosen row
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LauncherOutput {
+    /// The row itself, starting with the entry ID
+    #[default]
+    Text,
+    /// The zero-based position of the row
+    Index,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ImagesConfig {
+    /// Offer images in other formats on paste, converted when requested
+    pub convert: bool,
+    /// Remove EXIF, XMP, ICC and comment metadata from JPEG, PNG and WebP
+    /// images before they are stored
+    pub strip_metadata: bool,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            convert: true,
+            strip_metadata: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct TextConfig {
+    /// Store plain text converted from HTML or RTF for entries without any,
+    /// so they can be pasted into terminals
+    pub add_plain_text: bool,
+}
+
+/// Shell commands run in the background when entries are captured, picked
+/// or deleted, with details in `CLIPPERS_*` variables and content on stdin
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_capture: Option<String>,
+    pub on_pick: Option<String>,
+    pub on_delete: Option<String>,
+    /// Hooks still running after this long are killed
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub timeout: Duration,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_capture: None,
+            on_pick: None,
+            on_delete: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A capture rule: every condition given must hold for its actions to apply.
+/// There is no condition on the selection, only the clipboard is captured.
+#[derive(Deserialize)]
+pub struct RuleConfig {
+    pub name: String,
+
+    /// MIME type any of the entry's types must have, e.g. "image/png" or "text/*"
+    pub mime: Option<String>,
+    /// Kind of content, e.g. "image"
+    pub class: Option<MimeClass>,
+    /// Regular expression matched against the entry's text
+    pub pattern: Option<String>,
+    /// Bounds on the total size of all representations, e.g. "5MB"
+    #[serde(default, deserialize_with = "deserialize_optional_size")]
+    pub min_size: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_optional_size")]
+    pub max_size: Option<u64>,
+
+    /// Do not store the entry, later rules are skipped
+    #[serde(default)]
+    pub ignore: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pin: bool,
+    /// Hide the entry's preview as for detected secrets
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Remove the entry from history after this long, e.g. "10m"
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub expire_after: Option<Duration>,
+    /// Changes made to the entry's text, in order
+    #[serde(default)]
+    pub rewrite: Vec<RewriteConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum RewriteConfig {
+    Builtin(BuiltinRewrite),
+    /// Replace matches of a regular expression, `$1` refers to groups
+    Replace {
+        pattern: String,
+        replace: String,
+    },
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinRewrite {
+    /// Remove `utm_*` and other tracking parameters from URLs
+    StripTracking,
+    /// Turn CRLF line endings into LF
+    Crlf,
+    /// Remove newlines at the end of the text
+    TrimTrailingNewlines,
+}
+
+/// Parse a size such as "500MB", "2 GiB" or "1024" (bytes)
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size: '{}'", s))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Invalid size unit: '{}'", unit.trim())),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    humantime::parse_duration(&s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_duration(deserializer).map(Some)
+}
+
+fn deserialize_optional_size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(bytes) => Ok(Some(bytes)),
+        Size::Text(s) => parse_size(&s).map(Some).map_err(serde::de::Error::custom),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::r#impl::storage::ClipboardEntry;
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageFormat;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+const JPEG_QUALITY: u8 = 90;
+
+/// Image types offered on paste in addition to the stored one
+#[cfg(target_os = "linux")]
+const OFFERED_IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/bmp", "image/webp"];
+#[cfg(target_os = "macos")]
+const OFFERED_IMAGE_TYPES: &[&str] = &["public.png", "public.jpeg", "com.microsoft.bmp"];
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const OFFERED_IMAGE_TYPES: &[&str] = &[];
+
+/// Image format clippers can encode for a clipboard type
+pub fn image_format(mime_type: &str) -> Option<ImageFormat> {
+    match mime_type {
+        "image/png" | "public.png" => Some(ImageFormat::Png),
+        "image/jpeg" | "image/jpg" | "public.jpeg" => Some(ImageFormat::Jpeg),
+        "image/bmp" | "image/x-bmp" | "com.microsoft.bmp" => Some(ImageFormat::Bmp),
+        "image/gif" | "com.compuserve.gif" => Some(ImageFormat::Gif),
+        "image/webp" | "org.webmproject.webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+/// Decode an image and re-encode it as the format of `mime_type`
+pub fn convert_image(
+    content: &[u8],
+    mime_type: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let format =
+        image_format(mime_type).ok_or_else(|| format!("Cannot convert images to {}", mime_type))?;
+    let image = image::load_from_memory(content)?;
+
+    let mut converted = Vec::new();
+    match format {
+        // JPEG has no alpha channel
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut converted, JPEG_QUALITY)
+            .encode_image(&image.to_rgb8())?,
+        format => image.write_to(&mut Cursor::new(&mut converted), format)?,
+    }
+    Ok(converted)
+}
+
+/// Types to offer on paste in addition to an entry's own, each mapped to the
+/// stored type it is converted from. Empty for entries without an image.
+/// Formats that cannot be made from the stored image are left out.
+pub fn image_conversions(entry: &ClipboardEntry) -> HashMap<String, String> {
+    let Some((source, image)) = entry.get_image_type() else {
+        return HashMap::new();
+    };
+    if image_format(source).is_none() {
+        return HashMap::new();
+    }
+    OFFERED_IMAGE_TYPES
+        .iter()
+        .filter(|target| !entry.types.contains_key(**target))
+        .filter(|target| {
+            image_format(target)
+                .is_some_and(|format| image.width.max(image.height) <= max_side(format))
+        })
+        .map(|target| (target.to_string(), source.clone()))
+        .collect()
+}
+
+/// Longest side an image format can hold, in pixels
+fn max_side(format: ImageFormat) -> usize {
+    match format {
+        ImageFormat::WebP => 16383,
+        ImageFormat::Jpeg => 65535,
+        _ => usize::MAX,
+    }
+}
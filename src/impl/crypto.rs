@@ -0,0 +1,336 @@
+use crate::r#impl::config::{Config, EncryptionConfig};
+use crate::r#impl::path;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Sealed data layout: MAGIC | VERSION | nonce | ciphertext (with tag)
+const MAGIC: &[u8] = b"CLPRSENC";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN;
+
+// Sealed with the key and kept in the params file to detect a wrong passphrase early
+const CHECK_PLAINTEXT: &[u8] = b"clippers";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kdf", rename_all = "lowercase")]
+enum KeySource {
+    Argon2id {
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+    Keyfile,
+}
+
+/// Written to `encryption.json`; its presence marks the store as encrypted
+#[derive(Serialize, Deserialize)]
+struct EncryptionParams {
+    #[serde(flatten)]
+    source: KeySource,
+    salt: String,  // hex
+    check: String, // hex
+}
+
+pub struct StoreKey {
+    cipher: XChaCha20Poly1305,
+    name_key: [u8; 32],
+}
+
+impl StoreKey {
+    fn from_master(master: &[u8]) -> Self {
+        // Separate subkeys, so blob names reveal nothing about the encryption key
+        let cipher_key = hmac_sha256(master, b"clippers encryption");
+        let name_key = hmac_sha256(master, b"clippers blob names");
+        Self {
+            cipher: XChaCha20Poly1305::new(&cipher_key.into()),
+            name_key,
+        }
+    }
+
+    pub fn seal(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, data)
+            .map_err(|_| "Failed to encrypt data")?;
+
+        let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.push(VERSION);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if !is_sealed(sealed) || sealed.len() < HEADER_LEN {
+            return Err("Unencrypted data found in an encrypted store".into());
+        }
+        if sealed[MAGIC.len()] != VERSION {
+            return Err(format!("Unsupported encryption version {}", sealed[MAGIC.len()]).into());
+        }
+
+        let nonce = XNonce::from_slice(&sealed[MAGIC.len() + 1..HEADER_LEN]);
+        Ok(self
+            .cipher
+            .decrypt(nonce, &sealed[HEADER_LEN..])
+            .map_err(|_| "Failed to decrypt data (wrong key or corrupted file)")?)
+    }
+
+    /// Keyed file name for a blob, so names do not reveal content identity
+    pub fn blob_name(&self, hash: &str) -> String {
+        hex::encode(hmac_sha256(&self.name_key, hash.as_bytes()))
+    }
+}
+
+enum KeyState {
+    Unknown,
+    Plain,
+    Unlocked(Arc<StoreKey>),
+}
+
+static KEY_STATE: Mutex<KeyState> = Mutex::new(KeyState::Unknown);
+
+/// Key of the store, unlocking it on first use. `None` if the store is not encrypted.
+pub fn current_key() -> Result<Option<Arc<StoreKey>>, Box<dyn std::error::Error>> {
+    let mut state = KEY_STATE.lock().map_err(|_| "Key state poisoned")?;
+    match &*state {
+        KeyState::Plain => return Ok(None),
+        KeyState::Unlocked(key) => return Ok(Some(key.clone())),
+        KeyState::Unknown => {}
+    }
+
+    let key = if is_encrypted()? {
+        Some(Arc::new(unlock()?))
+    } else {
+        None
+    };
+    *state = match &key {
+        Some(key) => KeyState::Unlocked(key.clone()),
+        None => KeyState::Plain,
+    };
+    Ok(key)
+}
+
+/// Switch the key used for subsequent reads and writes, when converting a store
+pub fn set_key(key: Option<Arc<StoreKey>>) {
+    if let Ok(mut state) = KEY_STATE.lock() {
+        *state = match key {
+            Some(key) => KeyState::Unlocked(key),
+            None => KeyState::Plain,
+        };
+    }
+}
+
+pub fn is_encrypted() -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(path::get_encryption_params_path()?.exists())
+}
+
+fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt data if the store is encrypted, otherwise return it as is
+pub fn seal(data: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match current_key()? {
+        Some(key) => key.seal(&data),
+        None => Ok(data),
+    }
+}
+
+/// Decrypt data written by `seal`. Once the store is encrypted, plain data is
+/// rejected, so content cannot be slipped in by writing unencrypted files.
+/// Without a key nothing is sealed, so plain data that happens to start like
+/// sealed data is not mistaken for it.
+pub fn unseal(data: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match current_key()? {
+        Some(key) => key.open(&data),
+        None => Ok(data),
+    }
+}
+
+pub fn blob_name(hash: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match current_key()? {
+        Some(key) => key.blob_name(hash),
+        None => hash.to_string(),
+    })
+}
+
+/// Generate a new key and write its parameters, marking the store as encrypted.
+/// Uses a keyfile if one is given or configured, otherwise a passphrase.
+pub fn create_key(keyfile: Option<PathBuf>) -> Result<Arc<StoreKey>, Box<dyn std::error::Error>> {
+    let config = Config::load()?.encryption;
+    let keyfile = keyfile.or_else(|| config.keyfile.clone());
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let (source, master) = match keyfile {
+        Some(keyfile) => {
+            let keyfile = path::expand_home(&keyfile);
+            if !keyfile.exists() {
+                generate_keyfile(&keyfile)?;
+                println!("Generated new keyfile at {}", keyfile.display());
+            }
+            (KeySource::Keyfile, keyfile_master(&keyfile, &salt)?)
+        }
+        None => {
+            let source = KeySource::Argon2id {
+                m_cost: Params::DEFAULT_M_COST,
+                t_cost: Params::DEFAULT_T_COST,
+                p_cost: Params::DEFAULT_P_COST,
+            };
+            let passphrase = read_passphrase(&config, true)?;
+            let master = passphrase_master(&source, &passphrase, &salt)?;
+            (source, master)
+        }
+    };
+
+    let key = StoreKey::from_master(&master);
+    let params = EncryptionParams {
+        source,
+        salt: hex::encode(salt),
+        check: hex::encode(key.seal(CHECK_PLAINTEXT)?),
+    };
+    let params_path = path::get_encryption_params_path()?;
+    if let Some(parent) = params_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(params_path, serde_json::to_vec_pretty(&params)?)?;
+
+    Ok(Arc::new(key))
+}
+
+/// Forget the key parameters, marking the store as unencrypted
+pub fn remove_params() -> Result<(), Box<dyn std::error::Error>> {
+    let params_path = path::get_encryption_params_path()?;
+    if params_path.exists() {
+        fs::remove_file(params_path)?;
+    }
+    Ok(())
+}
+
+fn unlock() -> Result<StoreKey, Box<dyn std::error::Error>> {
+    let config = Config::load()?.encryption;
+    let params: EncryptionParams =
+        serde_json::from_slice(&fs::read(path::get_encryption_params_path()?)?)?;
+    let salt = hex::decode(&params.salt)?;
+
+    let master = match &params.source {
+        KeySource::Keyfile => {
+            let keyfile = config
+                .keyfile
+                .clone()
+                .or_else(|| std::env::var_os("CLIPPERS_KEYFILE").map(PathBuf::from))
+                .ok_or("Store is encrypted with a keyfile, but no keyfile is configured")?;
+            keyfile_master(&path::expand_home(&keyfile), &salt)?
+        }
+        source @ KeySource::Argon2id { .. } => {
+            let passphrase = read_passphrase(&config, false)?;
+            passphrase_master(source, &passphrase, &salt)?
+        }
+    };
+
+    let key = StoreKey::from_master(&master);
+    let check = hex::decode(&params.check)?;
+    match key.open(&check) {
+        Ok(plaintext) if plaintext == CHECK_PLAINTEXT => Ok(key),
+        _ => Err("Wrong passphrase or keyfile for encrypted store".into()),
+    }
+}
+
+fn read_passphrase(
+    config: &EncryptionConfig,
+    confirm: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = std::env::var("CLIPPERS_PASSPHRASE") {
+        return Ok(passphrase);
+    }
+
+    if let Some(command) = &config.passphrase_command {
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()?;
+        if !output.status.success() {
+            return Err(format!("Passphrase command failed: {}", command).into());
+        }
+        let passphrase = String::from_utf8(output.stdout)?;
+        return Ok(passphrase.trim_end_matches('\n').to_string());
+    }
+
+    let passphrase = rpassword::prompt_password("Passphrase for clippers store: ")?;
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".into());
+    }
+    if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+        return Err("Passphrases do not match".into());
+    }
+    Ok(passphrase)
+}
+
+fn passphrase_master(
+    source: &KeySource,
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let KeySource::Argon2id {
+        m_cost,
+        t_cost,
+        p_cost,
+    } = source
+    else {
+        return Err("Key source is not a passphrase".into());
+    };
+
+    let params = Params::new(*m_cost, *t_cost, *p_cost, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut master = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut master)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(master)
+}
+
+fn keyfile_master(keyfile: &Path, salt: &[u8]) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let content = fs::read(keyfile)
+        .map_err(|e| format!("Failed to read keyfile {}: {}", keyfile.display(), e))?;
+    Ok(hmac_sha256(salt, &content))
+}
+
+fn generate_keyfile(keyfile: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut content = [0u8; 32];
+    OsRng.fill_bytes(&mut content);
+
+    if let Some(parent) = keyfile.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(keyfile)?;
+    file.write_all(&content)?;
+    Ok(())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
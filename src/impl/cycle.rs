@@ -0,0 +1,65 @@
+use crate::r#impl::storage::Storage;
+use crate::r#impl::{crypto, path};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+/// Where `clippers cycle` is in the history, kept between calls until
+/// something new is copied
+#[derive(Serialize, Deserialize)]
+pub struct Cursor {
+    /// Entry last put on the clipboard
+    pub id: u64,
+    /// Hashes of the content put on the clipboard by type, so capturing
+    /// it again is not taken for a new copy
+    hashes: HashMap<String, String>,
+}
+
+impl Cursor {
+    pub fn new(id: u64, types: &HashMap<String, Vec<u8>>) -> Self {
+        let hashes = types
+            .iter()
+            .map(|(mime_type, content)| (mime_type.clone(), Storage::compute_hash(content)))
+            .collect();
+        Self { id, hashes }
+    }
+
+    pub fn load() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let file_path = path::get_cycle_file_path()?;
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        let data = crypto::unseal(fs::read(&file_path)?)?;
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_cycle_file_path()?;
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(&self)?;
+        fs::write(&file_path, crypto::seal(data)?)?;
+        Ok(())
+    }
+
+    /// Start the next cycle from the newest entry
+    pub fn reset() -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_cycle_file_path()?;
+        if file_path.exists() {
+            fs::remove_file(file_path)?;
+        }
+        Ok(())
+    }
+
+    /// Whether captured content is what the cycle put on the clipboard.
+    /// Types added on the way, e.g. converted images, are not compared.
+    pub fn set_by_cycle(&self, types: &HashMap<String, Vec<u8>>) -> bool {
+        let mut compared = types
+            .iter()
+            .filter_map(|(mime_type, content)| Some((self.hashes.get(mime_type)?, content)))
+            .peekable();
+        compared.peek().is_some()
+            && compared.all(|(hash, content)| *hash == Storage::compute_hash(content))
+    }
+}
@@ -0,0 +1,193 @@
+use base64::{engine::general_purpose, Engine as _};
+use image::imageops::FilterType;
+use image::{ImageFormat, RgbaImage};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+
+// Assumed size of a terminal cell in pixels, since few terminals report it
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+// Kitty graphics payloads are sent in chunks of at most this many bytes
+const KITTY_CHUNK: usize = 4096;
+
+/// How images are drawn in the terminal
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Protocol {
+    /// Kitty graphics protocol (kitty, WezTerm, Ghostty)
+    Kitty,
+    /// DEC sixel graphics (foot, mlterm, iTerm2, xterm -ti vt340)
+    Sixel,
+    /// Colored half-block characters, works in any true-color terminal
+    Blocks,
+}
+
+impl Protocol {
+    /// Best protocol the terminal is known to support
+    pub fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || matches!(term_program.as_str(), "WezTerm" | "ghostty")
+        {
+            Protocol::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term_program == "iTerm.app"
+        {
+            Protocol::Sixel
+        } else {
+            Protocol::Blocks
+        }
+    }
+}
+
+/// Draw an image scaled down to fit a box of `width` x `height` terminal cells
+pub fn render(
+    out: &mut impl Write,
+    content: &[u8],
+    protocol: Protocol,
+    width: u16,
+    height: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let image = image::load_from_memory(content)?;
+
+    let box_width = width.max(1) as u32 * CELL_WIDTH;
+    let box_height = height.max(1) as u32 * CELL_HEIGHT;
+    let scale = (box_width as f64 / image.width() as f64)
+        .min(box_height as f64 / image.height() as f64)
+        .min(1.0);
+    let pixel_width = ((image.width() as f64 * scale) as u32).max(1);
+    let pixel_height = ((image.height() as f64 * scale) as u32).max(1);
+    let columns = pixel_width.div_ceil(CELL_WIDTH);
+    let rows = pixel_height.div_ceil(CELL_HEIGHT);
+
+    match protocol {
+        Protocol::Kitty => {
+            let image = image.resize_exact(pixel_width, pixel_height, FilterType::Triangle);
+            let mut png = Vec::new();
+            image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            write_kitty(out, &png, columns, rows)?;
+            writeln!(out)?;
+        }
+        Protocol::Sixel => {
+            let image = image.resize_exact(pixel_width, pixel_height, FilterType::Triangle);
+            out.write_all(encode_sixel(&image.to_rgba8()).as_bytes())?;
+            writeln!(out)?;
+        }
+        Protocol::Blocks => {
+            // Each cell shows two roughly square pixels, one above the other
+            let (max_width, max_height) = (width.max(1) as u32, height.max(1) as u32 * 2);
+            let image = if image.width() > max_width || image.height() > max_height {
+                image.resize(max_width, max_height, FilterType::Triangle)
+            } else {
+                image
+            };
+            write_blocks(out, &image.to_rgba8())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_kitty(
+    out: &mut impl Write,
+    png: &[u8],
+    columns: u32,
+    rows: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let encoded = general_purpose::STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            // q=2 suppresses replies, which would end up as terminal input
+            write!(
+                out,
+                "\x1b_Ga=T,f=100,q=2,c={},r={},m={};",
+                columns, rows, more
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Sixel image using a fixed 6x6x6 color cube, transparent pixels left unset
+fn encode_sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut sixel = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for color in 0..216 {
+        let (r, g, b) = (color / 36, color / 6 % 6, color % 6);
+        sixel.push_str(&format!("#{};2;{};{};{}", color, r * 20, g * 20, b * 20));
+    }
+
+    let quantize = |c: u8| (c as usize * 5 + 127) / 255;
+    for band in (0..height).step_by(6) {
+        // Color -> one sixel (six stacked pixels) per column
+        let mut layers: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for x in 0..width {
+            for dy in 0..6.min(height - band) {
+                let pixel = image.get_pixel(x, band + dy);
+                if pixel[3] < 128 {
+                    continue;
+                }
+                let color = quantize(pixel[0]) * 36 + quantize(pixel[1]) * 6 + quantize(pixel[2]);
+                layers
+                    .entry(color)
+                    .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << dy;
+            }
+        }
+
+        for (color, bits) in layers {
+            sixel.push_str(&format!("#{}", color));
+            let mut x = 0;
+            while x < bits.len() {
+                let run = bits[x..].iter().take_while(|&&b| b == bits[x]).count();
+                let c = (63 + bits[x]) as char;
+                if run > 3 {
+                    sixel.push_str(&format!("!{}{}", run, c));
+                } else {
+                    sixel.extend(std::iter::repeat_n(c, run));
+                }
+                x += run;
+            }
+            // Back to the start of the band for the next color
+            sixel.push('$');
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+fn write_blocks(out: &mut impl Write, image: &RgbaImage) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = image.dimensions();
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let top = image.get_pixel(x, y);
+            let bottom = image.get_pixel(x, (y + 1).min(height - 1));
+            match (top[3] >= 128, bottom[3] >= 128) {
+                (true, true) => write!(
+                    out,
+                    "\x1b[38;2;{};{};{};48;2;{};{};{}m▀",
+                    top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+                )?,
+                (true, false) => write!(out, "\x1b[38;2;{};{};{}m▀", top[0], top[1], top[2])?,
+                (false, true) => {
+                    write!(out, "\x1b[38;2;{};{};{}m▄", bottom[0], bottom[1], bottom[2])?
+                }
+                (false, false) => write!(out, " ")?,
+            }
+            write!(out, "\x1b[0m")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::r#impl::config::HooksConfig;
+use crate::r#impl::mime;
+use crate::r#impl::shell;
+use crate::r#impl::storage::ClipboardEntry;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use std::os::unix::process::CommandExt;
+use std::process::Stdio;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy)]
+pub enum HookEvent {
+    Capture,
+    Pick,
+    Delete,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Capture => "capture",
+            HookEvent::Pick => "pick",
+            HookEvent::Delete => "delete",
+        }
+    }
+
+    fn command(self, config: &HooksConfig) -> Option<&String> {
+        match self {
+            HookEvent::Capture => config.on_capture.as_ref(),
+            HookEvent::Pick => config.on_pick.as_ref(),
+            HookEvent::Delete => config.on_delete.as_ref(),
+        }
+    }
+}
+
+/// Run the hook configured for an event in the background. The entry is read
+/// before returning, so it may be deleted right after. Short-lived commands
+/// join the returned thread so the hook is not cut off when they exit; it is
+/// killed after the configured timeout in any case.
+pub fn spawn(
+    config: &HooksConfig,
+    event: HookEvent,
+    entry: &ClipboardEntry,
+    preview: String,
+) -> Option<JoinHandle<()>> {
+    let command = event.command(config)?.clone();
+    let timeout = config.timeout;
+
+    let mut types: Vec<&String> = entry.types.keys().collect();
+    types.sort();
+    let mut env = vec![
+        ("CLIPPERS_EVENT", event.name().to_string()),
+        ("CLIPPERS_ID", entry.id.to_string()),
+        (
+            "CLIPPERS_TYPES",
+            types
+                .iter()
+                .map(|mime_type| mime_type.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        ("CLIPPERS_PREVIEW", preview),
+    ];
+    if !entry.tags.is_empty() {
+        env.push(("CLIPPERS_TAGS", entry.tags.join(",")));
+    }
+
+    // Secrets are not handed to other programs
+    let content = if entry.is_sensitive() {
+        env.push(("CLIPPERS_SENSITIVE", "1".to_string()));
+        None
+    } else {
+        primary_content(entry)
+    };
+    if let Some((mime_type, _)) = &content {
+        env.push(("CLIPPERS_MIME_TYPE", mime_type.clone()));
+    }
+    let content = content.map(|(_, content)| content).unwrap_or_default();
+
+    Some(start(event, command, env, content, timeout))
+}
+
+/// Like `spawn`, for text that is not an entry of its own, e.g. text joined
+/// from several entries. `CLIPPERS_ID` is not set.
+pub fn spawn_for_text(
+    config: &HooksConfig,
+    event: HookEvent,
+    text: &str,
+    preview: String,
+) -> Option<JoinHandle<()>> {
+    let command = event.command(config)?.clone();
+    let env = vec![
+        ("CLIPPERS_EVENT", event.name().to_string()),
+        ("CLIPPERS_TYPES", mime::offered_text_types().join("\n")),
+        ("CLIPPERS_PREVIEW", preview),
+        ("CLIPPERS_MIME_TYPE", mime::plain_text_type().to_string()),
+    ];
+    Some(start(
+        event,
+        command,
+        env,
+        text.as_bytes().to_vec(),
+        config.timeout,
+    ))
+}
+
+fn start(
+    event: HookEvent,
+    command: String,
+    env: Vec<(&'static str, String)>,
+    content: Vec<u8>,
+    timeout: Duration,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        if let Err(e) = run(&command, &env, content, timeout) {
+            eprintln!("Error running {} hook: {}", event.name(), e);
+        }
+    })
+}
+
+/// Text of the entry, or its image if it has no text
+fn primary_content(entry: &ClipboardEntry) -> Option<(String, Vec<u8>)> {
+    if let Some(text) = entry.get_text_content() {
+        return Some((mime::plain_text_type().to_string(), text.into_bytes()));
+    }
+    let (mime_type, _) = entry.get_image_type()?;
+    let content = entry.get_content_by_type(mime_type)?;
+    Some((mime_type.clone(), content))
+}
+
+fn run(
+    command: &str,
+    env: &[(&str, String)],
+    content: Vec<u8>,
+    timeout: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut child, _) = shell::spawn(
+        shell::command(command)
+            .envs(env.iter().map(|(name, value)| (name, value)))
+            .stdout(Stdio::null())
+            // Its own process group, so a timeout also stops what the hook started
+            .process_group(0),
+        content,
+    )
+    .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(format!("'{}' failed with {}", command, status).into());
+            }
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL);
+            let _ = child.wait();
+            return Err(format!(
+                "'{}' timed out after {}",
+                command,
+                humantime::format_duration(timeout)
+            )
+            .into());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
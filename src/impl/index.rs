@@ -0,0 +1,145 @@
+use crate::r#impl::{crypto, path};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+// Score factor for a query term that only matches the start of a word
+const PREFIX_WEIGHT: f64 = 0.5;
+
+/// Inverted index over the text content of history entries
+#[derive(Serialize, Deserialize, Default)]
+pub struct SearchIndex {
+    // term -> entry ID -> occurrences
+    terms: BTreeMap<String, HashMap<u64, u32>>,
+    // entry ID -> (distinct terms, number of words)
+    docs: HashMap<u64, (Vec<String>, u32)>,
+}
+
+impl SearchIndex {
+    /// Split text into case-folded words. Compatibility forms are normalized
+    /// too, so e.g. "ﬁle" matches "file" and "STRASSE" matches "straße".
+    pub fn tokenize(text: &str) -> Vec<String> {
+        let normalized: String = text.nfkc().collect();
+        normalized
+            .unicode_words()
+            .map(|word| word.to_lowercase().replace('ß', "ss"))
+            .collect()
+    }
+
+    pub fn from_file() -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = path::get_index_file_path()?;
+        if !file_path.exists() {
+            return Ok(SearchIndex::default());
+        }
+        let data = crypto::unseal(fs::read(&file_path)?)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub fn to_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_index_file_path()?;
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(&self)?;
+        fs::write(&file_path, crypto::seal(data)?)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.docs.contains_key(&id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &u64> {
+        self.docs.keys()
+    }
+
+    pub fn add(&mut self, id: u64, text: &str) {
+        self.remove(id);
+
+        let tokens = Self::tokenize(text);
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *counts.entry(token.clone()).or_default() += 1;
+        }
+
+        let distinct = counts.keys().cloned().collect();
+        for (term, count) in counts {
+            self.terms.entry(term).or_default().insert(id, count);
+        }
+        self.docs.insert(id, (distinct, tokens.len() as u32));
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        let Some((distinct, _)) = self.docs.remove(&id) else {
+            return;
+        };
+        for term in distinct {
+            if let Some(postings) = self.terms.get_mut(&term) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Entry IDs containing every word of the query, best match first.
+    /// Each query word also matches words starting with it.
+    pub fn search(&self, query: &str) -> Vec<(u64, f64)> {
+        let query_terms = Self::tokenize(query);
+        if query_terms.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+
+        let doc_count = self.docs.len() as f64;
+        let avg_length = self.docs.values().map(|(_, len)| *len as f64).sum::<f64>() / doc_count;
+
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        let mut matched_terms: HashMap<u64, usize> = HashMap::new();
+
+        for query_term in &query_terms {
+            let mut term_scores: HashMap<u64, f64> = HashMap::new();
+
+            let candidates = self
+                .terms
+                .range(query_term.clone()..)
+                .take_while(|(term, _)| term.starts_with(query_term.as_str()));
+            for (term, postings) in candidates {
+                let weight = if term == query_term {
+                    1.0
+                } else {
+                    PREFIX_WEIGHT
+                };
+                let df = postings.len() as f64;
+                let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                for (id, &tf) in postings {
+                    let length = self.docs.get(id).map_or(0.0, |(_, len)| *len as f64);
+                    let tf = tf as f64;
+                    let score =
+                        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / avg_length));
+                    *term_scores.entry(*id).or_default() += weight * score;
+                }
+            }
+
+            for (id, score) in term_scores {
+                *scores.entry(id).or_default() += score;
+                *matched_terms.entry(id).or_default() += 1;
+            }
+        }
+
+        let mut results: Vec<(u64, f64)> = scores
+            .into_iter()
+            .filter(|(id, _)| matched_terms.get(id) == Some(&query_terms.len()))
+            .collect();
+        // Newer entries first among equal scores
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+        results
+    }
+}
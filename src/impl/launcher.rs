@@ -0,0 +1,254 @@
+use crate::r#impl::config::{
+    LauncherConfig, LauncherOutput, LauncherSeparator, MenuConfig, DEFAULT_LAUNCHER_ROW,
+};
+use crate::r#impl::shell;
+use std::path::PathBuf;
+
+pub const BUILTIN_LAUNCHERS: &[&str] = &["rofi", "wofi", "fuzzel", "dmenu", "fzf", "choose"];
+
+const PROMPT: &str = "clippers";
+// Launchers taller than this are capped, e.g. `dmenu -l`
+const MAX_LINES: usize = 20;
+
+fn builtin(name: &str) -> Option<LauncherConfig> {
+    let (command, separator, markup, output, row, prompt) = match name {
+        // Row metadata makes entries searchable by ID without showing it
+        "rofi" => (
+            "rofi -dmenu -i -p '{prompt}' -markup-rows -show-icons -format i",
+            LauncherSeparator::Newline,
+            true,
+            LauncherOutput::Index,
+            "{label}\0meta\x1f{id}\x1ficon\x1f{thumbnail}",
+            Some("rofi -dmenu -p '{prompt}' -l 0"),
+        ),
+        "wofi" => (
+            "wofi --dmenu --insensitive --prompt '{prompt}'",
+            LauncherSeparator::Newline,
+            false,
+            LauncherOutput::Text,
+            DEFAULT_LAUNCHER_ROW,
+            None,
+        ),
+        "fuzzel" => (
+            "fuzzel --dmenu --index --prompt '{prompt}: '",
+            LauncherSeparator::Newline,
+            false,
+            LauncherOutput::Index,
+            "{id}|{label}\0icon\x1f{thumbnail}",
+            Some("fuzzel --dmenu --prompt-only '{prompt}: '"),
+        ),
+        "dmenu" => (
+            "dmenu -i -l {lines} -p '{prompt}'",
+            LauncherSeparator::Newline,
+            false,
+            LauncherOutput::Text,
+            DEFAULT_LAUNCHER_ROW,
+            None,
+        ),
+        "fzf" => (
+            "fzf --read0 --print0 --no-sort --prompt '{prompt}> '",
+            LauncherSeparator::Nul,
+            false,
+            LauncherOutput::Text,
+            DEFAULT_LAUNCHER_ROW,
+            // Prints what was typed rather than the closest match
+            Some("fzf --prompt '{prompt}> ' --bind 'enter:print-query'"),
+        ),
+        "choose" => (
+            "choose -i -p '{prompt}'",
+            LauncherSeparator::Newline,
+            false,
+            LauncherOutput::Index,
+            DEFAULT_LAUNCHER_ROW,
+            None,
+        ),
+        _ => return None,
+    };
+    Some(LauncherConfig {
+        command: command.to_string(),
+        separator,
+        markup,
+        output,
+        row: row.to_string(),
+        prompt: prompt.map(str::to_string),
+    })
+}
+
+/// Launcher configured under `name`, or the built-in one of that name
+pub fn resolve(
+    name: &str,
+    config: &MenuConfig,
+) -> Result<LauncherConfig, Box<dyn std::error::Error>> {
+    config
+        .launchers
+        .get(name)
+        .cloned()
+        .or_else(|| builtin(name))
+        .ok_or_else(|| {
+            format!(
+                "Unknown launcher '{}', expected one of {} or a launcher from [menu.launchers]",
+                name,
+                BUILTIN_LAUNCHERS.join(", ")
+            )
+            .into()
+        })
+}
+
+pub struct Row {
+    /// Entry ID, or a snippet name after '@'
+    pub key: String,
+    /// Single-line text shown for the entry
+    pub label: String,
+    pub thumbnail: Option<PathBuf>,
+}
+
+/// Show rows in the launcher and return the key of the chosen one,
+/// or `None` if the launcher was dismissed
+pub fn run(
+    launcher: &LauncherConfig,
+    rows: &[Row],
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let separator = match launcher.separator {
+        LauncherSeparator::Newline => '\n',
+        LauncherSeparator::Nul => '\0',
+    };
+    let mut input = String::new();
+    for row in rows {
+        let label = if launcher.markup {
+            escape_markup(&row.label)
+        } else {
+            row.label.clone()
+        };
+        let thumbnail = row
+            .thumbnail
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        // Label last, so placeholders in the content are left alone
+        let row = launcher
+            .row
+            .replace("{id}", &row.key)
+            .replace("{thumbnail}", &thumbnail)
+            .replace("{label}", &label);
+        input.push_str(&row);
+        input.push(separator);
+    }
+
+    let command = launcher
+        .command
+        .replace("{prompt}", PROMPT)
+        .replace("{lines}", &rows.len().clamp(1, MAX_LINES).to_string());
+    let Some(selection) = spawn(&command, input)? else {
+        return Ok(None);
+    };
+
+    match launcher.output {
+        LauncherOutput::Text => Ok(Some(parse_row_key(&selection).to_string())),
+        LauncherOutput::Index => {
+            let index: usize = selection
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid launcher output: '{}'", selection))?;
+            let row = rows
+                .get(index)
+                .ok_or_else(|| format!("Launcher returned unknown row {}", index))?;
+            Ok(Some(row.key.clone()))
+        }
+    }
+}
+
+/// Ask for a line of text with the launcher, e.g. to fill in a snippet
+/// placeholder. Returns `None` if the launcher was dismissed.
+pub fn prompt(
+    launcher: &LauncherConfig,
+    prompt: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let command = match (&launcher.prompt, launcher.output) {
+        (Some(command), _) => command,
+        (None, LauncherOutput::Text) => &launcher.command,
+        // Would print the position of a row rather than the typed text
+        (None, LauncherOutput::Index) => {
+            return Err(format!(
+                "Launcher '{}' cannot ask for text, set its `prompt` command",
+                launcher.command
+            )
+            .into())
+        }
+    };
+    let command = command
+        .replace("{prompt}", &prompt.replace('\'', ""))
+        .replace("{lines}", "1");
+    spawn(&command, String::new())
+}
+
+/// Run a launcher command with rows on stdin, returning what it printed,
+/// or `None` if it was dismissed
+fn spawn(command: &str, input: String) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let output = shell::output(command, input.into_bytes())
+        .map_err(|e| format!("Failed to run launcher '{}': {}", command, e))?;
+
+    if output.status.code() == Some(127) {
+        return Err(format!("Launcher not found: '{}'", command).into());
+    }
+    // Launchers exit with an error status when dismissed
+    let selection = String::from_utf8_lossy(&output.stdout);
+    let selection = selection.trim_end_matches(['\n', '\0']);
+    if !output.status.success() || selection.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(selection.to_string()))
+}
+
+/// ID of a row printed by `clippers list` or a launcher, ignoring parts after '|'
+pub fn parse_row_id(row: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let id_str = parse_row_key(row);
+    id_str
+        .parse()
+        .map_err(|_| format!("Invalid ID: '{}'", id_str).into())
+}
+
+/// Entry ID or snippet key at the start of a row, before any '|'
+pub fn parse_row_key(row: &str) -> &str {
+    let row = row.trim();
+    // Rows with a wofi image prefix, "img:<path>:text:<row>"
+    let row = match row
+        .strip_prefix("img:")
+        .and_then(|rest| rest.split_once(":text:"))
+    {
+        Some((_, row)) => row,
+        None => row,
+    };
+    row.split('|').next().unwrap_or("").trim()
+}
+
+/// Row keys chosen in a multi-select picker, one row per NUL. Input without
+/// NUL is a single row, whose label may span several lines.
+pub fn parse_row_keys(input: &str) -> Vec<&str> {
+    input
+        .split('\0')
+        .map(parse_row_key)
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+/// Inclusive range of entry IDs such as "3-7", in the order written
+pub fn parse_id_range(key: &str) -> Option<(u64, u64)> {
+    let (first, last) = key.split_once('-')?;
+    Some((first.trim().parse().ok()?, last.trim().parse().ok()?))
+}
+
+/// Escape text for launchers rendering Pango markup
+pub fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
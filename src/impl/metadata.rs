@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+const JPEG_SOI: &[u8] = &[0xFF, 0xD8];
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// PNG chunks that carry EXIF, XMP (in iTXt), text comments, timestamps or an ICC profile
+const PNG_METADATA_CHUNKS: &[&[u8]] = &[b"eXIf", b"iTXt", b"tEXt", b"zTXt", b"tIME", b"iCCP"];
+// WebP chunks and the matching VP8X feature flags
+const WEBP_METADATA_CHUNKS: &[(&[u8], u8)] = &[(b"EXIF", 0x08), (b"XMP ", 0x04), (b"ICCP", 0x20)];
+
+/// Remove EXIF, XMP, ICC and comment metadata from every JPEG, PNG or WebP
+/// type of an entry. Returns whether anything was removed.
+pub fn strip_types(types: &mut HashMap<String, Vec<u8>>) -> bool {
+    let mut stripped = false;
+    for content in types.values_mut() {
+        if let Some(without) = strip(content) {
+            *content = without;
+            stripped = true;
+        }
+    }
+    stripped
+}
+
+/// Image without its metadata, or `None` if there is none or the content is
+/// not a well-formed JPEG, PNG or WebP. Pixel data is copied as is.
+pub fn strip(content: &[u8]) -> Option<Vec<u8>> {
+    let stripped = if content.starts_with(JPEG_SOI) {
+        strip_jpeg(content)?
+    } else if content.starts_with(PNG_SIGNATURE) {
+        strip_png(content)?
+    } else if content.starts_with(b"RIFF") && content.get(8..12) == Some(b"WEBP") {
+        strip_webp(content)?
+    } else {
+        return None;
+    };
+    (stripped.len() != content.len()).then_some(stripped)
+}
+
+fn strip_jpeg(content: &[u8]) -> Option<Vec<u8>> {
+    let mut stripped = JPEG_SOI.to_vec();
+    let mut pos = JPEG_SOI.len();
+
+    loop {
+        if *content.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *content.get(pos + 1)?;
+        match marker {
+            // Fill byte before a marker
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // Start of scan: entropy-coded data and the rest are kept as is
+            0xDA => {
+                stripped.extend_from_slice(&content[pos..]);
+                return Some(stripped);
+            }
+            // Markers without a payload
+            0x01 | 0xD0..=0xD7 => {
+                stripped.extend_from_slice(&content[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            // End of image before any scan
+            0xD9 => return None,
+            _ => {}
+        }
+
+        let length = u16::from_be_bytes([*content.get(pos + 2)?, *content.get(pos + 3)?]) as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > content.len() {
+            return None;
+        }
+        // APP1-APP13 and APP15 hold EXIF, XMP, ICC profiles, IPTC and vendor data, COM
+        // comments. JFIF (APP0) and Adobe (APP14) affect how pixels are decoded.
+        let metadata = matches!(marker, 0xE1..=0xED | 0xEF | 0xFE);
+        if !metadata {
+            stripped.extend_from_slice(&content[pos..end]);
+        }
+        pos = end;
+    }
+}
+
+fn strip_png(content: &[u8]) -> Option<Vec<u8>> {
+    let mut stripped = PNG_SIGNATURE.to_vec();
+    let mut pos = PNG_SIGNATURE.len();
+
+    while pos < content.len() {
+        let length = u32::from_be_bytes(content.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let chunk_type = content.get(pos + 4..pos + 8)?;
+        // Length, type, data and CRC
+        let end = pos.checked_add(12 + length)?;
+        if end > content.len() {
+            return None;
+        }
+        if !PNG_METADATA_CHUNKS.contains(&chunk_type) {
+            stripped.extend_from_slice(&content[pos..end]);
+        }
+        if chunk_type == b"IEND" {
+            return Some(stripped);
+        }
+        pos = end;
+    }
+    None
+}
+
+fn strip_webp(content: &[u8]) -> Option<Vec<u8>> {
+    // RIFF header with a placeholder size, then "WEBP"
+    let mut stripped = b"RIFF\0\0\0\0WEBP".to_vec();
+    let mut pos = 12;
+    let mut removed_flags = 0;
+    let mut vp8x_flags_pos = None;
+
+    while pos < content.len() {
+        let fourcc = content.get(pos..pos + 4)?;
+        let size = u32::from_le_bytes(content.get(pos + 4..pos + 8)?.try_into().ok()?) as usize;
+        // Header, data and a padding byte for odd sizes
+        let end = pos.checked_add(8 + size + size % 2)?.min(content.len());
+        if pos + 8 + size > content.len() {
+            return None;
+        }
+
+        match WEBP_METADATA_CHUNKS
+            .iter()
+            .find(|(name, _)| *name == fourcc)
+        {
+            Some((_, flag)) => removed_flags |= flag,
+            None => {
+                if fourcc == b"VP8X" {
+                    vp8x_flags_pos = Some(stripped.len() + 8);
+                }
+                stripped.extend_from_slice(&content[pos..end]);
+            }
+        }
+        pos = end;
+    }
+
+    if let Some(flags_pos) = vp8x_flags_pos {
+        *stripped.get_mut(flags_pos)? &= !removed_flags;
+    }
+    let riff_size = u32::try_from(stripped.len() - 8).ok()?;
+    stripped[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn encode(format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(16, 16, |x, y| Rgb([x as u8 * 16, y as u8 * 16, 128]));
+        let mut encoded = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut encoded), format)
+            .unwrap();
+        encoded
+    }
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let length = (payload.len() + 2) as u16;
+        let mut segment = vec![0xFF, marker];
+        segment.extend_from_slice(&length.to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    #[test]
+    fn jpeg_metadata_is_removed_and_pixels_kept() {
+        let plain = encode(ImageFormat::Jpeg);
+        let exif = jpeg_segment(0xE1, b"Exif\0\0MM\0*\0\0\0\x08\0\0");
+        let icc = jpeg_segment(0xE2, b"ICC_PROFILE\0\x01\x01profile data");
+        let comment = jpeg_segment(0xFE, b"taken at home");
+        let with_metadata = [&plain[..2], &exif, &icc, &comment, &plain[2..]].concat();
+
+        let stripped = strip(&with_metadata).unwrap();
+        assert_eq!(stripped, plain);
+        assert_eq!(
+            image::load_from_memory(&stripped).unwrap().to_rgb8(),
+            image::load_from_memory(&with_metadata).unwrap().to_rgb8()
+        );
+    }
+
+    #[test]
+    fn png_metadata_is_removed() {
+        let plain = encode(ImageFormat::Png);
+        // Signature and IHDR, whose data is 13 bytes
+        let header_end = PNG_SIGNATURE.len() + 12 + 13;
+        let data = b"Comment\0at home";
+        let mut text = (data.len() as u32).to_be_bytes().to_vec();
+        text.extend_from_slice(b"tEXt");
+        text.extend_from_slice(data);
+        text.extend_from_slice(&[0; 4]); // CRC, not checked
+        let with_metadata = [&plain[..header_end], &text, &plain[header_end..]].concat();
+
+        assert_eq!(strip(&with_metadata).unwrap(), plain);
+    }
+
+    #[test]
+    fn images_without_metadata_are_left_alone() {
+        assert!(strip(&encode(ImageFormat::Png)).is_none());
+        assert!(strip(b"not an image").is_none());
+    }
+
+    #[test]
+    fn truncated_images_are_left_alone() {
+        let plain = encode(ImageFormat::Jpeg);
+        let exif = jpeg_segment(0xE1, b"Exif\0\0MM\0*\0\0\0\x08\0\0");
+        let with_metadata = [&plain[..2], &exif[..exif.len() - 4]].concat();
+        assert!(strip(&with_metadata).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+
+/// Broad kind of clipboard content, used to filter and limit entries
+#[derive(Deserialize, clap::ValueEnum, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MimeClass {
+    Text,
+    Image,
+    Html,
+    Files,
+    Other,
+}
+
+impl MimeClass {
+    pub fn of(mime_type: &str) -> Self {
+        if mime_type.starts_with("image/")
+            || matches!(mime_type, "public.png" | "public.jpeg" | "public.tiff")
+        {
+            MimeClass::Image
+        } else if matches!(
+            mime_type,
+            "text/uri-list" | "x-special/gnome-copied-files" | "public.file-url"
+        ) {
+            MimeClass::Files
+        } else if matches!(mime_type, "text/html" | "public.html") {
+            MimeClass::Html
+        } else if is_text(mime_type) {
+            MimeClass::Text
+        } else {
+            MimeClass::Other
+        }
+    }
+
+    /// Class of an entry offering several types: the most specific one wins,
+    /// e.g. an image copied from a browser also offers `text/html`
+    pub fn of_types<'a>(mime_types: impl IntoIterator<Item = &'a String>) -> Self {
+        mime_types
+            .into_iter()
+            .map(|mime_type| MimeClass::of(mime_type))
+            .min_by_key(|class| match class {
+                MimeClass::Files => 0,
+                MimeClass::Image => 1,
+                MimeClass::Html => 2,
+                MimeClass::Text => 3,
+                MimeClass::Other => 4,
+            })
+            .unwrap_or(MimeClass::Other)
+    }
+}
+
+/// Whether a clipboard type carries plain, unformatted text
+pub fn is_plain_text(mime_type: &str) -> bool {
+    let base = mime_type.split(';').next().unwrap_or("").trim();
+    matches!(
+        base,
+        "text/plain" | "UTF8_STRING" | "STRING" | "TEXT" | "public.utf8-plain-text"
+    )
+}
+
+/// The type plain text is stored and offered under on this platform
+pub fn plain_text_type() -> &'static str {
+    #[cfg(target_os = "macos")]
+    {
+        "public.utf8-plain-text"
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        "text/plain"
+    }
+}
+
+/// Types text created by clippers is offered under, e.g. after a transform
+pub fn offered_text_types() -> &'static [&'static str] {
+    #[cfg(target_os = "macos")]
+    {
+        &["public.utf8-plain-text"]
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        &["text/plain;charset=utf-8", "text/plain", "UTF8_STRING"]
+    }
+}
+
+/// Whether a clipboard type carries text of any kind, including markup
+pub fn is_text(mime_type: &str) -> bool {
+    is_plain_text(mime_type)
+        || mime_type.starts_with("text/")
+        || matches!(mime_type, "public.html" | "public.rtf")
+}
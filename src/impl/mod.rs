@@ -1,3 +1,25 @@
 pub mod clipboard;
+pub mod compression;
+pub mod config;
+pub mod convert;
+pub mod crypto;
+pub mod cycle;
+pub mod graphics;
+pub mod hooks;
+pub mod index;
+pub mod launcher;
+pub mod metadata;
+pub mod mime;
 pub mod path;
+pub mod query;
+pub mod queue;
+pub mod registers;
+pub mod richtext;
+pub mod rules;
+pub mod secrets;
+pub mod shell;
+pub mod snippets;
 pub mod storage;
+pub mod template;
+pub mod thumbnail;
+pub mod transform;
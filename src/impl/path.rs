@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn get_clippers_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     #[cfg(target_os = "macos")]
@@ -28,9 +28,68 @@ pub fn get_history_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(get_clippers_dir()?.join("history.json"))
 }
 
+pub fn get_snippets_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("snippets.json"))
+}
+
+pub fn get_registers_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("registers.json"))
+}
+
+pub fn get_cycle_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("cycle.json"))
+}
+
+pub fn get_queue_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("queue.json"))
+}
+
 pub fn get_blobs_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     use std::fs;
     let blobs_dir = get_clippers_dir()?.join("blobs");
     fs::create_dir_all(&blobs_dir)?;
     Ok(blobs_dir)
 }
+
+pub fn get_config_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    #[cfg(target_os = "macos")]
+    {
+        // macOS: keep the config next to the data
+        Ok(get_clippers_dir()?.join("config.toml"))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Linux: $XDG_CONFIG_HOME/clippers or ~/.config/clippers
+        let config_home = std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let home_dir = std::env::var("HOME").expect("HOME not set");
+                PathBuf::from(&home_dir).join(".config")
+            });
+        Ok(config_home.join("clippers").join("config.toml"))
+    }
+}
+
+pub fn get_encryption_params_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("encryption.json"))
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var("HOME")) {
+        (Ok(rest), Ok(home_dir)) => PathBuf::from(home_dir).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+pub fn get_index_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_clippers_dir()?.join("index.json"))
+}
+
+pub fn get_thumbnails_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    use std::fs;
+    let thumbnails_dir = get_clippers_dir()?.join("thumbnails");
+    fs::create_dir_all(&thumbnails_dir)?;
+    Ok(thumbnails_dir)
+}
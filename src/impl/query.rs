@@ -0,0 +1,76 @@
+use crate::r#impl::mime::MimeClass;
+use crate::r#impl::storage::ClipboardEntry;
+use regex::{Regex, RegexBuilder};
+use std::time::SystemTime;
+
+/// Filter over history entries. Unset fields match everything.
+#[derive(Default)]
+pub struct Query {
+    /// Matched against the text content, tags and note of the entry;
+    /// sensitive entries never match
+    pub text: Option<Regex>,
+    /// Entry must have all of these tags
+    pub tags: Vec<String>,
+    /// Entry must be of one of these classes
+    pub classes: Vec<MimeClass>,
+    pub since: Option<SystemTime>,
+    pub before: Option<SystemTime>,
+    pub pinned: Option<bool>,
+    /// Bounds on the total size of all representations
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+}
+
+impl Query {
+    /// Build the text matcher, escaping the pattern unless it is a regex
+    pub fn text_pattern(
+        pattern: &str,
+        is_regex: bool,
+        case_sensitive: bool,
+    ) -> Result<Regex, Box<dyn std::error::Error>> {
+        let pattern = if is_regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        Ok(RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()?)
+    }
+
+    pub fn matches(&self, entry: &ClipboardEntry) -> bool {
+        if self.pinned.is_some_and(|pinned| pinned != entry.pinned) {
+            return false;
+        }
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        if self.before.is_some_and(|before| entry.timestamp >= before) {
+            return false;
+        }
+        if !self.tags.iter().all(|tag| entry.tags.contains(tag)) {
+            return false;
+        }
+        if !self.classes.is_empty() && !self.classes.contains(&entry.get_class()) {
+            return false;
+        }
+
+        let size = entry.get_size();
+        if self.min_size.is_some_and(|min_size| size < min_size)
+            || self.max_size.is_some_and(|max_size| size > max_size)
+        {
+            return false;
+        }
+
+        // Checked last, as it may need to read the blob store. Secrets are
+        // never matched, so a pattern cannot probe what they contain, as in
+        // the search index.
+        match &self.text {
+            Some(_) if entry.is_sensitive() => false,
+            Some(text) => entry
+                .get_searchable_text()
+                .is_some_and(|content| text.is_match(&content)),
+            None => true,
+        }
+    }
+}
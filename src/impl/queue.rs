@@ -0,0 +1,70 @@
+use crate::r#impl::path;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+
+/// Whether the clipboard backend can serve a paste queue
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// Entries waiting to be pasted in the order they were copied. A queue is
+/// active while its file exists; `clippers watch` adds new entries to it
+/// and keeps the first one on the clipboard until it is pasted.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Queue {
+    ids: VecDeque<u64>,
+}
+
+impl Queue {
+    /// The active queue, `None` if there is none
+    pub fn load() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let file_path = path::get_queue_file_path()?;
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(&file_path)?)?))
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_queue_file_path()?;
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, serde_json::to_vec(&self)?)?;
+        Ok(())
+    }
+
+    pub fn start() -> Result<(), Box<dyn std::error::Error>> {
+        if Queue::load()?.is_some() {
+            return Err("A paste queue is already active".into());
+        }
+        Queue::default().save()
+    }
+
+    /// End the active queue, returning it with the entries not yet pasted
+    pub fn stop() -> Result<Self, Box<dyn std::error::Error>> {
+        let queue = Queue::load()?.ok_or("No paste queue is active")?;
+        fs::remove_file(path::get_queue_file_path()?)?;
+        Ok(queue)
+    }
+
+    pub fn push(&mut self, id: u64) {
+        self.ids.push_back(id);
+    }
+
+    pub fn pop(&mut self) -> Option<u64> {
+        self.ids.pop_front()
+    }
+
+    pub fn front(&self) -> Option<u64> {
+        self.ids.front().copied()
+    }
+
+    /// IDs of the queued entries, next to be pasted first
+    pub fn ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ids.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
@@ -0,0 +1,68 @@
+use crate::r#impl::storage::ClipboardEntry;
+use crate::r#impl::{crypto, path};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+/// Vim-style registers named by a letter or digit, holding content that
+/// stays put as new entries are captured. Stored like snippets.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Registers {
+    registers: BTreeMap<char, ClipboardEntry>,
+}
+
+impl Registers {
+    pub fn from_file() -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = path::get_registers_file_path()?;
+        if !file_path.exists() {
+            return Ok(Registers::default());
+        }
+        let data = crypto::unseal(fs::read(&file_path)?)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub fn to_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_registers_file_path()?;
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(&self)?;
+        fs::write(&file_path, crypto::seal(data)?)?;
+        Ok(())
+    }
+
+    /// Store content in a register, replacing what it held
+    pub fn set(
+        &mut self,
+        name: char,
+        types: HashMap<String, Vec<u8>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        validate_name(name)?;
+        self.registers.insert(name, ClipboardEntry::new(types)?);
+        self.to_file()
+    }
+
+    pub fn get(&self, name: char) -> Option<&ClipboardEntry> {
+        self.registers.get(&name)
+    }
+
+    /// Registers in order of their names
+    pub fn iter(&self) -> impl Iterator<Item = (&char, &ClipboardEntry)> {
+        self.registers.iter()
+    }
+
+    /// Hashes of all blobs referenced by registers
+    pub fn blob_hashes(&self) -> HashSet<String> {
+        self.registers
+            .values()
+            .flat_map(|register| register.blob_hashes())
+            .collect()
+    }
+}
+
+pub fn validate_name(name: char) -> Result<(), Box<dyn std::error::Error>> {
+    if !name.is_ascii_alphanumeric() {
+        return Err(format!("Invalid register '{}', use a letter or digit", name).into());
+    }
+    Ok(())
+}
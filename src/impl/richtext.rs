@@ -0,0 +1,443 @@
+use crate::r#impl::mime;
+use std::collections::HashMap;
+
+// Elements whose content is never shown
+const HIDDEN_ELEMENTS: &[&str] = &["head", "script", "style", "template", "noscript"];
+// Elements that start on a new line
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+// RTF groups holding fonts, styles, pictures and document info rather than text
+const RTF_DESTINATIONS: &[&str] = &[
+    "colortbl",
+    "datastore",
+    "filetbl",
+    "fonttbl",
+    "footer",
+    "header",
+    "info",
+    "latentstyles",
+    "listoverridetable",
+    "listtable",
+    "pict",
+    "revtbl",
+    "rsidtbl",
+    "stylesheet",
+    "themedata",
+    "xmlnsdecl",
+];
+
+pub fn is_html(mime_type: &str) -> bool {
+    matches!(mime_type, "text/html" | "public.html")
+}
+
+pub fn is_rtf(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "text/rtf" | "application/rtf" | "text/richtext" | "public.rtf"
+    )
+}
+
+/// Readable plain text of HTML or RTF content, other text as is
+pub fn to_plain_text(mime_type: &str, text: &str) -> String {
+    if is_html(mime_type) {
+        html_to_text(text)
+    } else if is_rtf(mime_type) {
+        rtf_to_text(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Add a plain text type derived from HTML or RTF to content that has none,
+/// so it can be pasted into terminals. Returns whether one was added.
+pub fn add_plain_text(types: &mut HashMap<String, Vec<u8>>) -> bool {
+    if types.keys().any(|mime_type| mime::is_plain_text(mime_type)) {
+        return false;
+    }
+    let derived = types
+        .iter()
+        .filter(|(mime_type, _)| is_html(mime_type) || is_rtf(mime_type))
+        // HTML first, it keeps more structure than basic RTF conversion
+        .min_by_key(|(mime_type, _)| !is_html(mime_type))
+        .and_then(|(mime_type, content)| {
+            let text = std::str::from_utf8(content).ok()?;
+            Some(to_plain_text(mime_type, text))
+        });
+
+    match derived {
+        Some(text) if !text.is_empty() => {
+            types.insert(mime::plain_text_type().to_string(), text.into_bytes());
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut hidden: Option<String> = None; // element whose content is skipped
+    let mut preformatted = 0;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let (run, tail) = rest.split_at(end);
+            rest = tail;
+            if hidden.is_some() {
+                continue;
+            }
+
+            let run = decode_entities(run);
+            if preformatted > 0 {
+                text.push_str(&run);
+            } else {
+                for (i, word) in run.split_whitespace().enumerate() {
+                    let starts_with_space = run.starts_with(char::is_whitespace);
+                    if (i > 0 || starts_with_space) && !text.ends_with([' ', '\n', '\t']) {
+                        text.push(' ');
+                    }
+                    text.push_str(word);
+                }
+                if run.ends_with(char::is_whitespace) && !text.ends_with([' ', '\n']) {
+                    text.push(' ');
+                }
+            }
+            continue;
+        }
+
+        let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+        let (tag, tail) = rest.split_at(end);
+        rest = tail;
+
+        let closing = tag.starts_with("</");
+        let name = tag
+            .trim_start_matches(['<', '/'])
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+
+        if let Some(element) = &hidden {
+            if closing && *element == name {
+                hidden = None;
+            }
+            continue;
+        }
+        if HIDDEN_ELEMENTS.contains(&name.as_str()) {
+            if !closing && !tag.ends_with("/>") {
+                hidden = Some(name);
+            }
+            continue;
+        }
+
+        match name.as_str() {
+            "br" => push_line_break(&mut text),
+            // Cells after the first in a row are separated by tabs
+            "td" | "th" if !closing && !text.is_empty() && !text.ends_with('\n') => {
+                text.truncate(text.trim_end_matches(' ').len());
+                text.push('\t');
+            }
+            "pre" if closing => preformatted -= 1,
+            "pre" => preformatted += 1,
+            _ => {}
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            start_block(&mut text);
+            if name == "li" && !closing {
+                text.push_str("- ");
+            }
+        }
+    }
+
+    tidy(&text)
+}
+
+fn push_line_break(text: &mut String) {
+    text.truncate(text.trim_end_matches(' ').len());
+    text.push('\n');
+}
+
+fn start_block(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        push_line_break(text);
+    }
+}
+
+/// Trim line ends and collapse runs of blank lines
+fn tidy(text: &str) -> String {
+    let mut tidy = String::new();
+    let mut blank_lines = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !tidy.is_empty() {
+            tidy.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        blank_lines = 0;
+        tidy.push_str(line);
+    }
+    tidy
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "hellip" => Some('…'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            "copy" => Some('©'),
+            "reg" => Some('®'),
+            "trade" => Some('™'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Text of basic RTF: paragraphs, tabs, escaped and Unicode characters.
+/// Formatting, fonts, pictures and document info are dropped.
+pub fn rtf_to_text(rtf: &str) -> String {
+    struct Group {
+        skip: bool,
+        unicode_skip: usize, // fallback characters after \uN, set by \ucN
+    }
+
+    let mut text = String::new();
+    let mut groups = vec![Group {
+        skip: false,
+        unicode_skip: 1,
+    }];
+    let mut pending_skip = 0; // fallback characters still to drop
+    let mut group_start = false; // right after '{', where destinations are named
+    let mut chars = rtf.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let group = groups.last_mut().expect("RTF group stack is never empty");
+        match c {
+            '{' => {
+                let group = Group {
+                    skip: group.skip,
+                    unicode_skip: group.unicode_skip,
+                };
+                groups.push(group);
+                group_start = true;
+                continue;
+            }
+            '}' => {
+                if groups.len() > 1 {
+                    groups.pop();
+                }
+                pending_skip = 0;
+            }
+            '\\' => {
+                let Some(&next) = chars.peek() else {
+                    break;
+                };
+                if !next.is_ascii_alphabetic() {
+                    chars.next();
+                    match next {
+                        // Ignorable destination
+                        '*' if group_start => group.skip = true,
+                        '\'' => {
+                            let hex: String = chars.by_ref().take(2).collect();
+                            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                                if pending_skip > 0 {
+                                    pending_skip -= 1;
+                                } else if !group.skip {
+                                    text.push(windows_1252(byte));
+                                }
+                            }
+                        }
+                        '~' if !group.skip => text.push('\u{a0}'),
+                        '\\' | '{' | '}' if !group.skip => text.push(next),
+                        '\n' | '\r' if !group.skip => text.push('\n'),
+                        _ => {}
+                    }
+                    group_start = false;
+                    continue;
+                }
+
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                    chars.next();
+                }
+                let mut parameter = String::new();
+                if chars.peek() == Some(&'-') {
+                    parameter.push('-');
+                    chars.next();
+                }
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                    parameter.push(c);
+                    chars.next();
+                }
+                // A space ends the control word and is not part of the text
+                if chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+                let parameter: Option<i32> = parameter.parse().ok();
+
+                if group_start && RTF_DESTINATIONS.contains(&word.as_str()) {
+                    group.skip = true;
+                }
+                group_start = false;
+                if group.skip {
+                    continue;
+                }
+
+                match word.as_str() {
+                    "par" | "line" | "row" => text.push('\n'),
+                    "tab" | "cell" => text.push('\t'),
+                    "emdash" => text.push('—'),
+                    "endash" => text.push('–'),
+                    "lquote" => text.push('‘'),
+                    "rquote" => text.push('’'),
+                    "ldblquote" => text.push('“'),
+                    "rdblquote" => text.push('”'),
+                    "bullet" => text.push('•'),
+                    "uc" => group.unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+                    "u" => {
+                        // Negative values encode code points above 32767
+                        let code = parameter.unwrap_or(0);
+                        let code = if code < 0 { code + 65536 } else { code };
+                        text.push(char::from_u32(code as u32).unwrap_or('\u{fffd}'));
+                        pending_skip = group.unicode_skip;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            '\n' | '\r' => {}
+            c => {
+                if pending_skip > 0 {
+                    pending_skip -= 1;
+                } else if !group.skip {
+                    text.push(c);
+                }
+            }
+        }
+        group_start = false;
+    }
+
+    tidy(&text)
+}
+
+/// Character for a byte in the Windows-1252 code page, the usual RTF default
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+        byte => byte as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_blocks_become_lines() {
+        let html = "<html><head><title>Page</title><style>p { color: red }</style></head>\
+                    <body><h1>Title &amp; more</h1><p>Hello <b>world</b>,&nbsp;nice.</p>\
+                    <!-- hidden --><ul><li>one</li><li>two</li></ul></body></html>";
+        assert_eq!(
+            html_to_text(html),
+            "Title & more\nHello world, nice.\n- one\n- two"
+        );
+    }
+
+    #[test]
+    fn html_preformatted_text_is_kept() {
+        let html = "<pre>  x\n  y</pre><p>a<br>b</p>";
+        assert_eq!(html_to_text(html), "  x\n  y\na\nb");
+    }
+
+    #[test]
+    fn rtf_keeps_text_and_drops_formatting() {
+        let rtf = r"{\rtf1\ansi{\fonttbl{\f0 Helvetica;}}{\*\generator Writer;}\f0\b Caf\'e9 bold\b0\par Second \u8364? line\tab x}";
+        assert_eq!(rtf_to_text(rtf), "Café bold\nSecond € line\tx");
+    }
+}
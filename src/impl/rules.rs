@@ -0,0 +1,282 @@
+use crate::r#impl::config::{BuiltinRewrite, RewriteConfig, RuleConfig};
+use crate::r#impl::mime::{self, MimeClass};
+use crate::r#impl::storage::ClipboardEntry;
+use regex::Regex;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Query parameters added for tracking, matched by name or `prefix*`
+const TRACKING_PARAMETERS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "mc_cid", "mc_eid",
+    "igshid", "yclid", "_hsenc", "_hsmi", "mkt_tok",
+];
+
+const URL_PATTERN: &str = r#"https?://[^\s"'<>]+"#;
+
+struct Rule {
+    name: String,
+    mime: Option<String>,
+    class: Option<MimeClass>,
+    pattern: Option<Regex>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    ignore: bool,
+    tags: Vec<String>,
+    pin: bool,
+    sensitive: bool,
+    expire_after: Option<Duration>,
+    rewrites: Vec<Rewrite>,
+}
+
+enum Rewrite {
+    StripTracking(Regex), // matching URLs
+    Crlf,
+    TrimTrailingNewlines,
+    Replace(Regex, String),
+}
+
+/// What the matching rules decided for an entry
+#[derive(Default)]
+pub struct Outcome {
+    /// Names of all rules that matched, in order
+    pub matched: Vec<String>,
+    /// Rule that asked for the entry not to be stored
+    pub ignored_by: Option<String>,
+    pub tags: Vec<String>,
+    pub pin: bool,
+    /// Names of matched rules that mark the entry sensitive
+    pub sensitive: Vec<String>,
+    /// Shortest expiry of the matched rules
+    pub expire_after: Option<Duration>,
+    /// Whether any text was changed
+    pub rewritten: bool,
+}
+
+impl Outcome {
+    /// Apply the tag, pin, sensitive and expiry actions to a new entry
+    pub fn apply_to(&self, entry: &mut ClipboardEntry) {
+        for tag in &self.tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+        entry.pinned |= self.pin;
+        for rule in &self.sensitive {
+            if !entry.sensitive.contains(rule) {
+                entry.sensitive.push(rule.clone());
+            }
+        }
+        if let Some(ttl) = self.expire_after {
+            let expires_at = entry.timestamp + ttl;
+            entry.expires_at = Some(entry.expires_at.map_or(expires_at, |e| e.min(expires_at)));
+        }
+    }
+}
+
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn from_config(rules: &[RuleConfig]) -> Result<Self, Box<dyn std::error::Error>> {
+        let compile = |name: &str, pattern: &str| {
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern for rule '{}': {}", name, e))
+        };
+
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let rewrites = rule
+                    .rewrite
+                    .iter()
+                    .map(|rewrite| match rewrite {
+                        RewriteConfig::Builtin(BuiltinRewrite::StripTracking) => {
+                            Ok(Rewrite::StripTracking(compile(&rule.name, URL_PATTERN)?))
+                        }
+                        RewriteConfig::Builtin(BuiltinRewrite::Crlf) => Ok(Rewrite::Crlf),
+                        RewriteConfig::Builtin(BuiltinRewrite::TrimTrailingNewlines) => {
+                            Ok(Rewrite::TrimTrailingNewlines)
+                        }
+                        RewriteConfig::Replace { pattern, replace } => Ok(Rewrite::Replace(
+                            compile(&rule.name, pattern)?,
+                            replace.clone(),
+                        )),
+                    })
+                    .collect::<Result<_, String>>()?;
+                Ok(Rule {
+                    name: rule.name.clone(),
+                    mime: rule.mime.clone(),
+                    class: rule.class,
+                    pattern: rule
+                        .pattern
+                        .as_ref()
+                        .map(|pattern| compile(&rule.name, pattern))
+                        .transpose()?,
+                    min_size: rule.min_size,
+                    max_size: rule.max_size,
+                    ignore: rule.ignore,
+                    tags: rule.tags.clone(),
+                    pin: rule.pin,
+                    sensitive: rule.sensitive,
+                    expire_after: rule.expire_after,
+                    rewrites,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Evaluate the rules in order against new content, rewriting its text
+    /// in place. Later rules see the text as rewritten by earlier ones.
+    pub fn apply(&self, types: &mut HashMap<String, Vec<u8>>) -> Outcome {
+        let mut outcome = Outcome::default();
+
+        for rule in &self.rules {
+            if !rule.matches(types) {
+                continue;
+            }
+            outcome.matched.push(rule.name.clone());
+
+            if rule.ignore {
+                outcome.ignored_by = Some(rule.name.clone());
+                return outcome;
+            }
+            for tag in &rule.tags {
+                if !outcome.tags.contains(tag) {
+                    outcome.tags.push(tag.clone());
+                }
+            }
+            outcome.pin |= rule.pin;
+            if rule.sensitive {
+                outcome.sensitive.push(rule.name.clone());
+            }
+            if let Some(ttl) = rule.expire_after {
+                outcome.expire_after = Some(outcome.expire_after.map_or(ttl, |t| t.min(ttl)));
+            }
+            for rewrite in &rule.rewrites {
+                outcome.rewritten |= rewrite.apply(types);
+            }
+        }
+        outcome
+    }
+}
+
+impl Rule {
+    fn matches(&self, types: &HashMap<String, Vec<u8>>) -> bool {
+        if let Some(pattern) = &self.mime {
+            if !types
+                .keys()
+                .any(|mime_type| mime_matches(pattern, mime_type))
+            {
+                return false;
+            }
+        }
+        if self
+            .class
+            .is_some_and(|class| MimeClass::of_types(types.keys()) != class)
+        {
+            return false;
+        }
+
+        let size: u64 = types.values().map(|content| content.len() as u64).sum();
+        if self.min_size.is_some_and(|min| size < min)
+            || self.max_size.is_some_and(|max| size > max)
+        {
+            return false;
+        }
+
+        match &self.pattern {
+            Some(pattern) => text_types(types)
+                .filter_map(|(_, content)| std::str::from_utf8(content).ok())
+                .any(|text| pattern.is_match(text)),
+            None => true,
+        }
+    }
+}
+
+impl Rewrite {
+    /// Rewrite every text type, returning whether anything changed
+    fn apply(&self, types: &mut HashMap<String, Vec<u8>>) -> bool {
+        let mut changed = false;
+        for (mime_type, content) in types.iter_mut() {
+            if !is_rewritable(mime_type) {
+                continue;
+            }
+            let Ok(text) = std::str::from_utf8(content) else {
+                continue;
+            };
+            let rewritten = match self {
+                Rewrite::StripTracking(url) => url
+                    .replace_all(text, |captures: &regex::Captures| {
+                        strip_tracking(&captures[0])
+                    })
+                    .into_owned(),
+                Rewrite::Crlf => text.replace("\r\n", "\n"),
+                Rewrite::TrimTrailingNewlines => text.trim_end_matches(['\n', '\r']).to_string(),
+                Rewrite::Replace(regex, replace) => regex.replace_all(text, replace).into_owned(),
+            };
+            if rewritten != text {
+                *content = rewritten.into_bytes();
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Text types, except file lists whose format is fixed, e.g. CRLF in `text/uri-list`
+fn is_rewritable(mime_type: &str) -> bool {
+    mime::is_text(mime_type) && MimeClass::of(mime_type) != MimeClass::Files
+}
+
+fn text_types(types: &HashMap<String, Vec<u8>>) -> impl Iterator<Item = (&String, &Vec<u8>)> {
+    types
+        .iter()
+        .filter(|(mime_type, _)| mime::is_text(mime_type))
+}
+
+/// Match a MIME type against a pattern such as "text/plain", "text/*" or "*"
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    let base = mime_type.split(';').next().unwrap_or("").trim();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => base.starts_with(prefix),
+        None => base == pattern || mime_type == pattern,
+    }
+}
+
+/// URL without tracking parameters in its query
+fn strip_tracking(url: &str) -> String {
+    let (url, fragment) = match url.find('#') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let Some((base, query)) = url.split_once('?') else {
+        return format!("{}{}", url, fragment);
+    };
+
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|parameter| {
+            // URLs in HTML separate parameters with `&amp;`
+            let name = parameter.trim_start_matches("amp;");
+            let name = name.split('=').next().unwrap_or("");
+            !name.is_empty() && !is_tracking_parameter(name)
+        })
+        .collect();
+
+    if kept.is_empty() {
+        format!("{}{}", base, fragment)
+    } else {
+        let query = kept.join("&");
+        format!("{}?{}{}", base, query.trim_start_matches("amp;"), fragment)
+    }
+}
+
+fn is_tracking_parameter(name: &str) -> bool {
+    TRACKING_PARAMETERS
+        .iter()
+        .any(|tracking| match tracking.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *tracking,
+        })
+}
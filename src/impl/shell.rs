@@ -0,0 +1,34 @@
+use std::io::{self, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::thread::JoinHandle;
+
+/// `sh -c` command with a piped stdin, for `spawn`
+pub fn command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command).stdin(Stdio::piped());
+    shell
+}
+
+/// Start a command and write `input` to its stdin. Written from another thread,
+/// so a command printing as it reads, or not reading at all, cannot block on a full pipe.
+pub fn spawn(
+    command: &mut Command,
+    input: Vec<u8>,
+) -> io::Result<(Child, JoinHandle<io::Result<()>>)> {
+    let mut child = command.spawn()?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| io::Error::other("Failed to open stdin"))?;
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    Ok((child, writer))
+}
+
+/// Run `sh -c <command>` with `input` on stdin, returning what it printed
+pub fn output(command: &str, input: Vec<u8>) -> io::Result<Output> {
+    let (child, writer) = spawn(self::command(command).stdout(Stdio::piped()), input)?;
+    let output = child.wait_with_output()?;
+    // The command may exit without reading all of it
+    let _ = writer.join();
+    Ok(output)
+}
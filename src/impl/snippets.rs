@@ -0,0 +1,93 @@
+use crate::r#impl::storage::ClipboardEntry;
+use crate::r#impl::{crypto, path};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+/// Marks a snippet in picker rows, e.g. "@signature"
+pub const SNIPPET_PREFIX: char = '@';
+
+/// Named content kept apart from the history, so retention never removes it.
+/// Content is stored like history entries, large content in the blob store.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Snippets {
+    snippets: BTreeMap<String, ClipboardEntry>,
+}
+
+impl Snippets {
+    pub fn from_file() -> Result<Self, Box<dyn std::error::Error>> {
+        let file_path = path::get_snippets_file_path()?;
+        if !file_path.exists() {
+            return Ok(Snippets::default());
+        }
+        let data = crypto::unseal(fs::read(&file_path)?)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub fn to_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = path::get_snippets_file_path()?;
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(&self)?;
+        fs::write(&file_path, crypto::seal(data)?)?;
+        Ok(())
+    }
+
+    /// Add a snippet, replacing any snippet of the same name
+    pub fn add(
+        &mut self,
+        name: &str,
+        types: HashMap<String, Vec<u8>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        validate_name(name)?;
+        self.snippets
+            .insert(name.to_string(), ClipboardEntry::new(types)?);
+        self.to_file()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.snippets
+            .remove(name)
+            .ok_or_else(|| format!("Snippet '{}' not found", name))?;
+        self.to_file()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ClipboardEntry> {
+        self.snippets.get(name)
+    }
+
+    /// Snippets by name, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ClipboardEntry)> {
+        self.snippets.iter()
+    }
+
+    /// Hashes of all blobs referenced by snippets
+    pub fn blob_hashes(&self) -> HashSet<String> {
+        self.snippets
+            .values()
+            .flat_map(|snippet| snippet.blob_hashes())
+            .collect()
+    }
+}
+
+/// Picker row key of a snippet
+pub fn row_key(name: &str) -> String {
+    format!("{}{}", SNIPPET_PREFIX, name)
+}
+
+/// Names are used in picker rows and on the command line, so they are kept simple
+fn validate_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(format!(
+            "Invalid snippet name '{}', use letters, digits, '-', '_' and '.'",
+            name
+        )
+        .into());
+    }
+    Ok(())
+}
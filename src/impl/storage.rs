@@ -1,14 +1,23 @@
-use crate::r#impl::path;
+use crate::r#impl::config::RetentionConfig;
+use crate::r#impl::index::SearchIndex;
+use crate::r#impl::mime::MimeClass;
+use crate::r#impl::query::Query;
+use crate::r#impl::registers::Registers;
+use crate::r#impl::snippets::Snippets;
+use crate::r#impl::{compression, crypto, mime, path, richtext, thumbnail};
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
-use std::collections::{HashMap, VecDeque};
-use std::fs::{self, File};
-use std::io::{BufReader, Write};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const EXTERNALIZE_THRESHOLD: usize = 1024; // 1KB
+const PREVIEW_LENGTH: usize = 1024; // characters
+const CLEANUP_GRACE: Duration = Duration::from_secs(60);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "value", rename_all = "lowercase")]
 pub enum ContentRef {
     Inline(String),       // plain text (valid UTF-8)
@@ -16,10 +25,38 @@ pub enum ContentRef {
     External(String),     // content hash for lookup
 }
 
+/// What is known about a content without loading it, captured at insert time
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContentMeta {
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageInfo>,
+    // Start of the text, only kept for externalized text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ImageInfo {
+    pub format: String,
+    pub width: usize,
+    pub height: usize,
+}
+
+pub struct StoredContent {
+    pub content: ContentRef,
+    pub meta: ContentMeta,
+}
+
 pub struct ClipboardEntry {
     pub id: u64,
     pub timestamp: std::time::SystemTime,
-    pub types: HashMap<String, Vec<u8>>, // mime_type -> content (bytes)
+    pub types: HashMap<String, StoredContent>, // mime_type -> content (not loaded)
+    pub sensitive: Vec<String>,                // names of secret rules that matched
+    pub expires_at: Option<std::time::SystemTime>,
+    pub pinned: bool, // protected from retention limits
+    pub tags: Vec<String>,
+    pub note: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,7 +64,7 @@ pub struct Storage {
     entries: VecDeque<ClipboardEntry>,
     highest_id: u64,
     #[serde(skip)]
-    max_entries: usize,
+    retention: RetentionConfig,
 }
 
 impl Storage {
@@ -39,58 +76,287 @@ impl Storage {
 
     pub fn write_blob(hash: &str, content: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         let blobs_dir = path::get_blobs_dir()?;
-        let blob_path = blobs_dir.join(hash);
+        let blob_path = blobs_dir.join(crypto::blob_name(hash)?);
 
         // Only write if it doesn't exist (content-addressable)
         if !blob_path.exists() {
-            let mut file = File::create(blob_path)?;
-            file.write_all(content)?;
+            let data = compression::compress(content, compression::DEFAULT_LEVEL)?;
+            fs::write(blob_path, crypto::seal(data)?)?;
+        } else {
+            // Reused blobs count as new, so cleanup spares them until they are referenced
+            fs::File::options()
+                .append(true)
+                .open(&blob_path)?
+                .set_modified(std::time::SystemTime::now())?;
         }
         Ok(())
     }
 
     pub fn read_blob(hash: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let blobs_dir = path::get_blobs_dir()?;
-        let blob_path = blobs_dir.join(hash);
-        Ok(fs::read(blob_path)?)
+        let blob_path = path::get_blobs_dir()?.join(crypto::blob_name(hash)?);
+        compression::decompress(crypto::unseal(fs::read(blob_path)?)?)
+    }
+
+    /// Re-encode a blob file in place at the given compression level,
+    /// returns its (old, new) size on disk
+    pub fn recompress_blob(
+        blob_path: &Path,
+        level: i32,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let data = fs::read(blob_path)?;
+        let old_size = data.len() as u64;
+
+        let content = compression::decompress(crypto::unseal(data)?)?;
+        let data = crypto::seal(compression::compress(&content, level)?)?;
+        let new_size = data.len() as u64;
+
+        // Write to a temporary file first so an interrupted run cannot lose the blob
+        let tmp_path = blob_path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(tmp_path, blob_path)?;
+
+        Ok((old_size, new_size))
     }
 
-    pub fn new(max_entries: usize) -> Self {
+    /// Paths of all files in the blob store, referenced or not
+    pub fn blob_paths() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut paths = Vec::new();
+        for dir_entry in fs::read_dir(path::get_blobs_dir()?)? {
+            paths.push(dir_entry?.path());
+        }
+        Ok(paths)
+    }
+
+    pub fn new(retention: RetentionConfig) -> Self {
         Self {
             entries: VecDeque::new(),
-            max_entries,
+            retention,
             highest_id: 0,
         }
     }
 
-    pub fn from_file(max_entries: usize) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_file(retention: RetentionConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut storage = Storage::from_file_as_is(retention)?;
+
+        // Removed entries are dropped from disk on the next write
+        storage.enforce_retention();
+
+        Ok(storage)
+    }
+
+    /// Load history exactly as stored, without applying retention limits
+    pub fn from_file_as_is(retention: RetentionConfig) -> Result<Self, Box<dyn std::error::Error>> {
         let file_path = path::get_history_file_path()?;
 
         let storage = if file_path.exists() {
-            let file = File::open(&file_path)?;
-            let reader = BufReader::new(file);
-            let mut storage: Storage = serde_json::from_reader(reader)?;
-            storage.max_entries = max_entries;
+            let data = crypto::unseal(fs::read(&file_path)?)?;
+            let mut storage: Storage = serde_json::from_slice(&data)?;
+            storage.retention = retention;
             storage
         } else {
-            Storage::new(max_entries)
+            Storage::new(retention)
         };
 
         Ok(storage)
     }
 
-    pub fn add_entry(&mut self, types: HashMap<String, Vec<u8>>) {
+    /// Add a prepared entry to history, assigning it the next ID
+    pub fn push_entry(
+        &mut self,
+        mut entry: ClipboardEntry,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
         self.highest_id += 1;
-        let entry = ClipboardEntry::new(self.highest_id, types);
+        entry.id = self.highest_id;
+
+        // Made once rules and secret detection had their say, so sensitive
+        // images never get one. Unsupported formats just get no thumbnail.
+        let _ = thumbnail::path_for(&entry);
 
         self.entries.push_front(entry);
+        self.enforce_retention();
+
+        self.to_file()?;
+        Ok(self.highest_id)
+    }
 
-        // Trim to max_entries if needed
-        if self.entries.len() > self.max_entries {
-            self.entries.pop_back();
+    /// Remove expired entries and entries over the configured limits,
+    /// oldest first. Returns whether any entry was removed.
+    pub fn enforce_retention(&mut self) -> bool {
+        let now = std::time::SystemTime::now();
+        let retention = &self.retention;
+        let before = self.entries.len();
+
+        let mut kept = 0;
+        let mut kept_per_class: HashMap<MimeClass, usize> = HashMap::new();
+        let mut blob_size = 0;
+        let mut counted_blobs = HashSet::new();
+
+        // Entries are ordered newest first
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+
+            let expired = entry.expires_at.is_some_and(|expires_at| expires_at <= now);
+            let too_old = retention.max_age.is_some_and(|max_age| {
+                now.duration_since(entry.timestamp)
+                    .is_ok_and(|age| age > max_age)
+            });
+            if expired || too_old || kept >= retention.max_entries {
+                return false;
+            }
+
+            let class = entry.get_class();
+            if let Some(&limit) = retention.max_per_class.get(&class) {
+                if kept_per_class.get(&class).copied().unwrap_or(0) >= limit {
+                    return false;
+                }
+            }
+
+            if let Some(max_blob_size) = retention.max_blob_size {
+                let new_blobs: HashMap<&str, usize> = entry
+                    .types
+                    .values()
+                    .filter_map(|stored| match &stored.content {
+                        ContentRef::External(hash) if !counted_blobs.contains(hash) => {
+                            Some((hash.as_str(), stored.meta.size))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let entry_size: u64 = new_blobs.values().map(|&size| size as u64).sum();
+                if blob_size + entry_size > max_blob_size {
+                    return false;
+                }
+                blob_size += entry_size;
+                counted_blobs.extend(new_blobs.keys().map(|hash| hash.to_string()));
+            }
+
+            kept += 1;
+            *kept_per_class.entry(class).or_default() += 1;
+            true
+        });
+
+        self.entries.len() != before
+    }
+
+    /// Delete blob files no longer referenced by the history, returns how many.
+    /// Recent files are kept, they may belong to content another command is
+    /// still saving, e.g. `snippet add` writes its blobs before the snippets.
+    pub fn remove_unreferenced_blobs(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let referenced = self
+            .blob_hashes()?
+            .iter()
+            .map(|hash| crypto::blob_name(hash))
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        let mut removed = 0;
+        for blob_path in Storage::blob_paths()? {
+            let name = blob_path.file_name().and_then(|name| name.to_str());
+            if !name.is_some_and(|name| referenced.contains(name)) && !is_recent(&blob_path) {
+                fs::remove_file(blob_path)?;
+                removed += 1;
+            }
         }
 
-        let _ = self.to_file();
+        // Thumbnails are named by the hash of the image, inline ones included
+        let snippets = Snippets::from_file()?;
+        let registers = Registers::from_file()?;
+        let images: HashSet<String> = self
+            .entries
+            .iter()
+            .chain(snippets.iter().map(|(_, entry)| entry))
+            .chain(registers.iter().map(|(_, entry)| entry))
+            .filter_map(|entry| entry.image_hash())
+            .collect();
+        for thumbnail_path in fs::read_dir(path::get_thumbnails_dir()?)? {
+            let thumbnail_path = thumbnail_path?.path();
+            let hash = thumbnail_path.file_stem().and_then(|stem| stem.to_str());
+            if !hash.is_some_and(|hash| images.contains(hash)) && !is_recent(&thumbnail_path) {
+                fs::remove_file(thumbnail_path)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn delete_entry(&mut self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        self.reload()?;
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+        self.entries.remove(position);
+        self.to_file()
+    }
+
+    pub fn set_pinned(&mut self, id: u64, pinned: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.reload()?;
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+        entry.pinned = pinned;
+        self.to_file()
+    }
+
+    /// Add tags to an entry, or remove them
+    pub fn set_tags(
+        &mut self,
+        id: u64,
+        tags: &[String],
+        remove: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.annotate(id, |entry| {
+            for tag in tags {
+                let position = entry.tags.iter().position(|t| t == tag);
+                match (position, remove) {
+                    (Some(position), true) => {
+                        entry.tags.remove(position);
+                    }
+                    (None, false) => entry.tags.push(tag.clone()),
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    /// Set or remove the note of an entry
+    pub fn set_note(
+        &mut self,
+        id: u64,
+        note: Option<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.annotate(id, |entry| entry.note = note)
+    }
+
+    fn annotate(
+        &mut self,
+        id: u64,
+        update: impl FnOnce(&mut ClipboardEntry),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.reload()?;
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("Entry with ID {} not found", id))?;
+        update(entry);
+
+        // Tags and notes are indexed with the text, so the entry is indexed again on save
+        let mut index = SearchIndex::from_file().unwrap_or_default();
+        index.remove(id);
+        index.to_file()?;
+        self.to_file()
+    }
+
+    /// Read the history again before changing it, so entries stored by other
+    /// processes since it was loaded are not overwritten, e.g. ones captured
+    /// by `clippers watch` while a menu is open
+    fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        *self = Storage::from_file(self.retention.clone())?;
+        Ok(())
     }
 
     pub fn get_entry_by_id(&self, id: u64) -> Option<&ClipboardEntry> {
@@ -98,11 +364,35 @@ impl Storage {
         self.entries.iter().find(|entry| entry.id == id)
     }
 
+    /// Entries matching the query, newest first
+    pub fn search(&self, query: &Query) -> Vec<&ClipboardEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn get_entries(&self) -> &VecDeque<ClipboardEntry> {
         &self.entries
     }
 
+    /// Hashes of all blobs referenced by the history, snippets or registers
+    pub fn blob_hashes(&self) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+        let mut hashes = Snippets::from_file()?.blob_hashes();
+        hashes.extend(Registers::from_file()?.blob_hashes());
+        hashes.extend(self.entries.iter().flat_map(|entry| entry.blob_hashes()));
+        Ok(hashes)
+    }
+
+    /// Read every blob referenced by the history, keyed by hash
+    pub fn load_blobs(&self) -> Result<HashMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+        self.blob_hashes()?
+            .into_iter()
+            .map(|hash| Storage::read_blob(&hash).map(|content| (hash, content)))
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.entries.clear();
@@ -116,11 +406,169 @@ impl Storage {
             fs::create_dir_all(parent)?;
         }
 
-        let file = File::create(&file_path)?;
-        serde_json::to_writer_pretty(file, &self)?;
+        let data = serde_json::to_vec_pretty(&self)?;
+        fs::write(&file_path, crypto::seal(data)?)?;
+
+        self.load_index()?;
 
         Ok(())
     }
+
+    /// Load the search index, bringing it in line with the history first
+    pub fn load_index(&self) -> Result<SearchIndex, Box<dyn std::error::Error>> {
+        // An unreadable index is only a cache, so it is rebuilt
+        let mut index = SearchIndex::from_file().unwrap_or_default();
+        if self.sync_index(&mut index) {
+            index.to_file()?;
+        }
+        Ok(index)
+    }
+
+    pub fn rebuild_index(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut index = SearchIndex::default();
+        self.sync_index(&mut index);
+        index.to_file()
+    }
+
+    /// Index new entries and drop removed ones, returns whether the index changed.
+    /// Sensitive entries are never indexed.
+    fn sync_index(&self, index: &mut SearchIndex) -> bool {
+        let indexable: HashSet<u64> = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_sensitive())
+            .map(|entry| entry.id)
+            .collect();
+
+        let stale: Vec<u64> = index
+            .ids()
+            .filter(|id| !indexable.contains(id))
+            .copied()
+            .collect();
+        let mut changed = !stale.is_empty();
+        for id in stale {
+            index.remove(id);
+        }
+
+        for entry in &self.entries {
+            if !indexable.contains(&entry.id) || index.contains(entry.id) {
+                continue;
+            }
+            if let Some(text) = entry.get_searchable_text() {
+                index.add(entry.id, &text);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Entries containing all words of `terms` and matching the query, best match first
+    pub fn search_ranked(
+        &self,
+        terms: &str,
+        query: &Query,
+    ) -> Result<Vec<&ClipboardEntry>, Box<dyn std::error::Error>> {
+        let entries_by_id: HashMap<u64, &ClipboardEntry> =
+            self.entries.iter().map(|entry| (entry.id, entry)).collect();
+
+        Ok(self
+            .load_index()?
+            .search(terms)
+            .into_iter()
+            .filter_map(|(id, _score)| entries_by_id.get(&id).copied())
+            .filter(|entry| query.matches(entry))
+            .collect())
+    }
+}
+
+impl ContentRef {
+    /// Store content inline, or externalize it to the blob store if large
+    pub fn from_content(content: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        if content.len() > EXTERNALIZE_THRESHOLD {
+            // Externalize: compute hash and write blob
+            let hash = Storage::compute_hash(content);
+            Storage::write_blob(&hash, content)?;
+            return Ok(ContentRef::External(hash));
+        }
+
+        // Check if content is valid UTF-8 text
+        Ok(match std::str::from_utf8(content) {
+            // Valid UTF-8: store as plain text
+            Ok(text) => ContentRef::Inline(text.to_string()),
+            // Invalid UTF-8 or binary: use base64
+            Err(_) => ContentRef::InlineBase64(general_purpose::STANDARD.encode(content)),
+        })
+    }
+
+    /// Get the content bytes, reading the blob store for external content
+    pub fn load(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            ContentRef::Inline(text) => Ok(text.clone().into_bytes()),
+            ContentRef::InlineBase64(encoded) => Ok(general_purpose::STANDARD.decode(encoded)?),
+            ContentRef::External(hash) => Storage::read_blob(hash)
+                .map_err(|e| format!("Failed to read blob {}: {}", hash, e).into()),
+        }
+    }
+}
+
+impl ContentMeta {
+    pub fn from_content(content: &[u8]) -> Self {
+        let preview = if content.len() > EXTERNALIZE_THRESHOLD {
+            std::str::from_utf8(content)
+                .ok()
+                .map(|text| text.chars().take(PREVIEW_LENGTH).collect())
+        } else {
+            None
+        };
+
+        Self {
+            size: content.len(),
+            image: ImageInfo::from_content(content),
+            preview,
+        }
+    }
+}
+
+impl ImageInfo {
+    /// Detect image format and dimensions using imagesize crate
+    pub fn from_content(content: &[u8]) -> Option<Self> {
+        let size = imagesize::blob_size(content).ok()?;
+
+        // Get format from image type
+        let format = match imagesize::image_type(content) {
+            Ok(img_type) => {
+                use imagesize::ImageType;
+                match img_type {
+                    ImageType::Png => "png",
+                    ImageType::Jpeg => "jpg",
+                    ImageType::Gif => "gif",
+                    ImageType::Webp => "webp",
+                    ImageType::Bmp => "bmp",
+                    ImageType::Ico => "ico",
+                    ImageType::Tiff => "tiff",
+                    ImageType::Heif(_) => "heic",
+                    ImageType::Qoi => "qoi",
+                    ImageType::Tga => "tga",
+                    ImageType::Pnm => "pnm",
+                    ImageType::Hdr => "hdr",
+                    ImageType::Exr => "exr",
+                    ImageType::Farbfeld => "farbfeld",
+                    ImageType::Psd => "psd",
+                    ImageType::Aseprite => "ase",
+                    ImageType::Ilbm => "ilbm",
+                    ImageType::Vtf => "vtf",
+                    _ => "image",
+                }
+            }
+            Err(_) => "image",
+        };
+
+        Some(Self {
+            format: format.to_string(),
+            width: size.width,
+            height: size.height,
+        })
+    }
 }
 
 impl Serialize for ClipboardEntry {
@@ -129,41 +577,50 @@ impl Serialize for ClipboardEntry {
         S: Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("ClipboardEntry", 3)?;
+        let mut state = serializer.serialize_struct("ClipboardEntry", 9)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("timestamp", &self.timestamp)?;
-
-        // Build ContentRef map for types
-        let mut types_refs = HashMap::new();
-        for (k, v) in &self.types {
-            let content_ref = if v.len() > EXTERNALIZE_THRESHOLD {
-                // Externalize: compute hash and write blob
-                let hash = Storage::compute_hash(v);
-                if let Err(e) = Storage::write_blob(&hash, v) {
-                    return Err(serde::ser::Error::custom(format!(
-                        "Failed to write blob: {}",
-                        e
-                    )));
-                }
-                ContentRef::External(hash)
-            } else {
-                // Check if content is valid UTF-8 text
-                match std::str::from_utf8(v) {
-                    Ok(text) => {
-                        // Valid UTF-8: store as plain text
-                        ContentRef::Inline(text.to_string())
-                    }
-                    Err(_) => {
-                        // Invalid UTF-8 or binary: use base64
-                        let encoded = general_purpose::STANDARD.encode(v);
-                        ContentRef::InlineBase64(encoded)
-                    }
-                }
-            };
-            types_refs.insert(k.clone(), content_ref);
+        if self.sensitive.is_empty() {
+            state.skip_field("sensitive")?;
+        } else {
+            state.serialize_field("sensitive", &self.sensitive)?;
+        }
+        if let Some(expires_at) = &self.expires_at {
+            state.serialize_field("expires_at", expires_at)?;
+        } else {
+            state.skip_field("expires_at")?;
+        }
+        if self.pinned {
+            state.serialize_field("pinned", &self.pinned)?;
+        } else {
+            state.skip_field("pinned")?;
+        }
+        if self.tags.is_empty() {
+            state.skip_field("tags")?;
+        } else {
+            state.serialize_field("tags", &self.tags)?;
+        }
+        if let Some(note) = &self.note {
+            state.serialize_field("note", note)?;
+        } else {
+            state.skip_field("note")?;
         }
 
+        // Content refs and their metadata are kept in separate maps,
+        // so the `types` format stays readable by older versions
+        let types_refs: HashMap<&String, &ContentRef> = self
+            .types
+            .iter()
+            .map(|(k, stored)| (k, &stored.content))
+            .collect();
+        let types_meta: HashMap<&String, &ContentMeta> = self
+            .types
+            .iter()
+            .map(|(k, stored)| (k, &stored.meta))
+            .collect();
+
         state.serialize_field("types", &types_refs)?;
+        state.serialize_field("meta", &types_meta)?;
         state.end()
     }
 }
@@ -192,6 +649,12 @@ impl<'de> Deserialize<'de> for ClipboardEntry {
                 let mut id = None;
                 let mut timestamp = None;
                 let mut types: Option<HashMap<String, ContentRef>> = None;
+                let mut meta: Option<HashMap<String, ContentMeta>> = None;
+                let mut sensitive: Option<Vec<String>> = None;
+                let mut expires_at = None;
+                let mut pinned = None;
+                let mut tags: Option<Vec<String>> = None;
+                let mut note = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -213,6 +676,42 @@ impl<'de> Deserialize<'de> for ClipboardEntry {
                             }
                             types = Some(map.next_value()?);
                         }
+                        "meta" => {
+                            if meta.is_some() {
+                                return Err(de::Error::duplicate_field("meta"));
+                            }
+                            meta = Some(map.next_value()?);
+                        }
+                        "sensitive" => {
+                            if sensitive.is_some() {
+                                return Err(de::Error::duplicate_field("sensitive"));
+                            }
+                            sensitive = Some(map.next_value()?);
+                        }
+                        "expires_at" => {
+                            if expires_at.is_some() {
+                                return Err(de::Error::duplicate_field("expires_at"));
+                            }
+                            expires_at = Some(map.next_value()?);
+                        }
+                        "pinned" => {
+                            if pinned.is_some() {
+                                return Err(de::Error::duplicate_field("pinned"));
+                            }
+                            pinned = Some(map.next_value()?);
+                        }
+                        "tags" => {
+                            if tags.is_some() {
+                                return Err(de::Error::duplicate_field("tags"));
+                            }
+                            tags = Some(map.next_value()?);
+                        }
+                        "note" => {
+                            if note.is_some() {
+                                return Err(de::Error::duplicate_field("note"));
+                            }
+                            note = Some(map.next_value()?);
+                        }
                         _ => {
                             let _ = map.next_value::<de::IgnoredAny>()?;
                         }
@@ -222,21 +721,21 @@ impl<'de> Deserialize<'de> for ClipboardEntry {
                 let id = id.ok_or_else(|| de::Error::missing_field("id"))?;
                 let timestamp = timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?;
                 let types_refs = types.ok_or_else(|| de::Error::missing_field("types"))?;
+                let mut types_meta = meta.unwrap_or_default();
 
-                // Convert ContentRef map to Vec<u8> map
-                let types: HashMap<String, Vec<u8>> = types_refs
+                // Blobs are not read here, except for entries written before
+                // metadata was stored, which are rewritten with it on the next save
+                let types = types_refs
                     .into_iter()
-                    .map(|(k, content_ref)| match content_ref {
-                        ContentRef::Inline(text) => Ok((k, text.into_bytes())),
-                        ContentRef::InlineBase64(encoded) => general_purpose::STANDARD
-                            .decode(&encoded)
-                            .map(|bytes| (k, bytes))
-                            .map_err(de::Error::custom),
-                        ContentRef::External(hash) => Storage::read_blob(&hash)
-                            .map(|bytes| (k, bytes))
-                            .map_err(|e| {
-                                de::Error::custom(format!("Failed to read blob {}: {}", hash, e))
-                            }),
+                    .map(|(k, content)| {
+                        let meta = match types_meta.remove(&k) {
+                            Some(meta) => meta,
+                            None => content
+                                .load()
+                                .map(|bytes| ContentMeta::from_content(&bytes))
+                                .map_err(de::Error::custom)?,
+                        };
+                        Ok((k, StoredContent { content, meta }))
                     })
                     .collect::<Result<_, _>>()?;
 
@@ -244,40 +743,157 @@ impl<'de> Deserialize<'de> for ClipboardEntry {
                     id,
                     timestamp,
                     types,
+                    sensitive: sensitive.unwrap_or_default(),
+                    expires_at,
+                    pinned: pinned.unwrap_or(false),
+                    tags: tags.unwrap_or_default(),
+                    note,
                 })
             }
         }
 
-        const FIELDS: &[&str] = &["id", "timestamp", "types"];
+        const FIELDS: &[&str] = &[
+            "id",
+            "timestamp",
+            "types",
+            "meta",
+            "sensitive",
+            "expires_at",
+            "pinned",
+            "tags",
+            "note",
+        ];
         deserializer.deserialize_struct("ClipboardEntry", FIELDS, ClipboardEntryVisitor)
     }
 }
 
 impl ClipboardEntry {
-    pub fn new(id: u64, types: HashMap<String, Vec<u8>>) -> Self {
-        Self {
-            id,
+    /// Create an entry captured now, writing large content to the blob store.
+    /// The ID is assigned by `Storage::push_entry`.
+    pub fn new(types: HashMap<String, Vec<u8>>) -> Result<Self, Box<dyn std::error::Error>> {
+        let types = types
+            .into_iter()
+            .map(|(k, bytes)| {
+                let stored = StoredContent {
+                    content: ContentRef::from_content(&bytes)?,
+                    meta: ContentMeta::from_content(&bytes),
+                };
+                Ok((k, stored))
+            })
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+
+        Ok(Self {
+            id: 0,
             timestamp: std::time::SystemTime::now(),
             types,
+            sensitive: Vec::new(),
+            expires_at: None,
+            pinned: false,
+            tags: Vec::new(),
+            note: None,
+        })
+    }
+
+    /// Flag the entry as containing a secret, optionally expiring it after `ttl`
+    pub fn mark_sensitive(&mut self, rules: Vec<String>, ttl: Option<std::time::Duration>) {
+        self.sensitive = rules;
+        if let Some(ttl) = ttl {
+            self.expires_at = Some(self.timestamp + ttl);
         }
     }
 
-    pub fn get_content_by_type(&self, mime_type: &str) -> Option<&Vec<u8>> {
-        self.types.get(mime_type)
+    pub fn is_sensitive(&self) -> bool {
+        !self.sensitive.is_empty()
     }
 
-    pub fn get_text_content(&self) -> Option<String> {
-        #[cfg(target_os = "linux")]
-        {
-            self.get_content_by_type("text/plain")
-                .and_then(|bytes| String::from_utf8(bytes.clone()).ok())
+    /// Load the content of one type. Reads the blob store for external content.
+    pub fn get_content_by_type(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.types
+            .get(mime_type)
+            .and_then(|stored| stored.content.load().ok())
+    }
+
+    /// Hashes of the blobs holding the entry's external content
+    pub fn blob_hashes(&self) -> impl Iterator<Item = String> + '_ {
+        self.types
+            .values()
+            .filter_map(|stored| match &stored.content {
+                ContentRef::External(hash) => Some(hash.clone()),
+                _ => None,
+            })
+    }
+
+    /// Hash of the entry's image content, which names its thumbnail
+    pub fn image_hash(&self) -> Option<String> {
+        let (mime_type, _) = self.get_image_type()?;
+        match &self.types[mime_type].content {
+            ContentRef::External(hash) => Some(hash.clone()),
+            content => Some(Storage::compute_hash(&content.load().ok()?)),
         }
+    }
+
+    /// Load the content of all types, e.g. to put the entry back on the clipboard
+    pub fn load_types(&self) -> Result<HashMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+        self.types
+            .iter()
+            .map(|(k, stored)| Ok((k.clone(), stored.content.load()?)))
+            .collect()
+    }
+
+    /// HTML or RTF type to derive text from, preferring HTML
+    fn rich_text_type(&self) -> Option<&String> {
+        self.types
+            .keys()
+            .filter(|mime_type| richtext::is_html(mime_type) || richtext::is_rtf(mime_type))
+            .min_by_key(|mime_type| (!richtext::is_html(mime_type), *mime_type))
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            self.get_content_by_type("public.utf8-plain-text")
-                .and_then(|bytes| String::from_utf8(bytes.clone()).ok())
+    /// Plain text of the entry, derived from HTML or RTF if it has none
+    pub fn get_text_content(&self) -> Option<String> {
+        if let Some(bytes) = self.get_content_by_type(mime::plain_text_type()) {
+            return String::from_utf8(bytes).ok();
         }
+        let mime_type = self.rich_text_type()?;
+        let bytes = self.get_content_by_type(mime_type)?;
+        let text = String::from_utf8(bytes).ok()?;
+        Some(richtext::to_plain_text(mime_type, &text)).filter(|text| !text.is_empty())
+    }
+
+    /// Text, tags and note of the entry, for search
+    pub fn get_searchable_text(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .get_text_content()
+            .into_iter()
+            .chain(self.tags.iter().cloned())
+            .chain(self.note.clone())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+
+    /// Text for display, without reading the blob store.
+    /// Externalized text is cut to its stored preview.
+    pub fn get_text_preview(&self) -> Option<String> {
+        let (mime_type, stored) = match self.types.get_key_value(mime::plain_text_type()) {
+            Some(plain) => plain,
+            None => self.types.get_key_value(self.rich_text_type()?)?,
+        };
+        let text = match &stored.content {
+            ContentRef::Inline(text) => text,
+            _ => stored.meta.preview.as_ref()?,
+        };
+        Some(richtext::to_plain_text(mime_type, text)).filter(|text| !text.is_empty())
+    }
+
+    /// Total size of all representations
+    pub fn get_size(&self) -> u64 {
+        self.types
+            .values()
+            .map(|stored| stored.meta.size as u64)
+            .sum()
+    }
+
+    pub fn get_class(&self) -> MimeClass {
+        MimeClass::of_types(self.types.keys())
     }
 
     #[allow(dead_code)]
@@ -285,66 +901,50 @@ impl ClipboardEntry {
         self.types.keys().collect()
     }
 
+    /// The type holding an image, if any, preferring lossless formats
+    pub fn get_image_type(&self) -> Option<(&String, &ImageInfo)> {
+        self.types
+            .iter()
+            .filter_map(|(mime_type, stored)| Some((mime_type, stored.meta.image.as_ref()?)))
+            .min_by_key(|(mime_type, image)| (image.format != "png", *mime_type))
+    }
+
     pub fn get_binary_info(&self) -> String {
-        for (mime_type, content) in &self.types {
-            if mime_type == "public.utf8-plain-text" || content.len() == 0 {
+        for (mime_type, stored) in &self.types {
+            let size = stored.meta.size;
+            if mime_type == "public.utf8-plain-text" || size == 0 {
                 continue;
             }
 
-            let size = content.len();
-            let size_str = if size < 1024 {
-                format!("{} B", size)
-            } else if size < 1024 * 1024 {
-                format!("{} KiB", size / 1024)
-            } else {
-                format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
-            };
+            let size_str = format_size(size as u64);
 
-            // Try to detect image format and dimensions using imagesize crate
-            let (format, dims) = match imagesize::blob_size(content) {
-                Ok(size) => {
-                    // Get format from image type
-                    let format_str = match imagesize::image_type(content) {
-                        Ok(img_type) => {
-                            use imagesize::ImageType;
-                            match img_type {
-                                ImageType::Png => "png",
-                                ImageType::Jpeg => "jpg",
-                                ImageType::Gif => "gif",
-                                ImageType::Webp => "webp",
-                                ImageType::Bmp => "bmp",
-                                ImageType::Ico => "ico",
-                                ImageType::Tiff => "tiff",
-                                ImageType::Heif(_) => "heic",
-                                ImageType::Qoi => "qoi",
-                                ImageType::Tga => "tga",
-                                ImageType::Pnm => "pnm",
-                                ImageType::Hdr => "hdr",
-                                ImageType::Exr => "exr",
-                                ImageType::Farbfeld => "farbfeld",
-                                ImageType::Psd => "psd",
-                                ImageType::Aseprite => "ase",
-                                ImageType::Ilbm => "ilbm",
-                                ImageType::Vtf => "vtf",
-                                _ => "image",
-                            }
-                        }
-                        Err(_) => "image",
-                    };
-                    (format_str, Some((size.width, size.height)))
-                }
-                Err(_) => ("binary", None),
-            };
-
-            let dims_str = dims.map(|(w, h)| format!("{}x{}", w, h));
-
-            let info = if let Some(dims) = dims_str {
-                format!("[[ binary data {} {} {} ]]", size_str, format, dims)
+            let info = if let Some(image) = &stored.meta.image {
+                format!(
+                    "[[ binary data {} {} {}x{} ]]",
+                    size_str, image.format, image.width, image.height
+                )
             } else {
-                format!("[[ binary data {} {} ]]", size_str, format)
+                format!("[[ binary data {} binary ]]", size_str)
             };
             return info;
         }
         "[no content available]".to_string()
     }
 }
+
+pub fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{} KiB", size / 1024)
+    } else {
+        format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Whether a file was written within the cleanup grace period
+fn is_recent(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age < CLEANUP_GRACE))
+}
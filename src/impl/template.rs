@@ -0,0 +1,79 @@
+use crate::r#impl::clipboard::create_clipboard;
+use chrono::Local;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// `{{name}}` or `{{name:argument}}`, other names are left as they are
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{\{\s*(date|clipboard|env|prompt)\s*(?::([^}]*))?\}\}").unwrap()
+    })
+}
+
+pub fn has_placeholders(text: &str) -> bool {
+    placeholder().is_match(text)
+}
+
+/// Fill in the placeholders of a snippet:
+/// - `{{date}}`, `{{date:FORMAT}}`: local time, formatted by strftime
+/// - `{{clipboard}}`: text currently on the clipboard
+/// - `{{env:NAME}}`: environment variable, empty if unset
+/// - `{{prompt:LABEL}}`: answer given to `ask`, asked once per label
+pub fn expand<F>(text: &str, mut ask: F) -> Result<String, Box<dyn std::error::Error>>
+where
+    F: FnMut(&str) -> Result<String, Box<dyn std::error::Error>>,
+{
+    let mut expanded = String::with_capacity(text.len());
+    let mut answers: HashMap<String, String> = HashMap::new();
+    let mut clipboard: Option<String> = None;
+    let mut last = 0;
+
+    for captures in placeholder().captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        let argument = captures
+            .get(2)
+            .map(|m| m.as_str().trim())
+            .filter(|argument| !argument.is_empty());
+        expanded.push_str(&text[last..whole.start()]);
+        last = whole.end();
+
+        match &captures[1] {
+            "date" => expanded.push_str(&format_date(argument.unwrap_or(DEFAULT_DATE_FORMAT))?),
+            "clipboard" => {
+                if clipboard.is_none() {
+                    // Read once, before anything is pasted
+                    clipboard = Some(create_clipboard()?.get_string().unwrap_or_default());
+                }
+                expanded.push_str(clipboard.as_deref().unwrap_or_default());
+            }
+            "env" => {
+                let name = argument.ok_or("{{env}} needs a variable name, e.g. {{env:USER}}")?;
+                expanded.push_str(&std::env::var(name).unwrap_or_default());
+            }
+            "prompt" => {
+                let label = argument.unwrap_or("Value");
+                if !answers.contains_key(label) {
+                    answers.insert(label.to_string(), ask(label)?);
+                }
+                expanded.push_str(&answers[label]);
+            }
+            _ => unreachable!(),
+        }
+    }
+    expanded.push_str(&text[last..]);
+    Ok(expanded)
+}
+
+/// Current local time in a strftime format
+fn format_date(format: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut date = String::new();
+    // Unknown specifiers surface as a formatting error
+    write!(date, "{}", Local::now().format(format))
+        .map_err(|_| format!("Invalid date format '{}'", format))?;
+    Ok(date)
+}
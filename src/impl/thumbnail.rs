@@ -0,0 +1,57 @@
+use crate::r#impl::crypto;
+use crate::r#impl::path;
+use crate::r#impl::storage::{ClipboardEntry, ContentRef, Storage};
+use image::ImageFormat;
+use std::fs;
+use std::path::PathBuf;
+
+// Longest side of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+
+fn thumbnail_path(hash: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(path::get_thumbnails_dir()?.join(format!("{}.png", hash)))
+}
+
+/// Write a PNG thumbnail of image content, cached by content hash.
+/// `None` for encrypted stores, whose content must not be written to disk in plain.
+pub fn create(content: &[u8]) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    if crypto::current_key()?.is_some() {
+        return Ok(None);
+    }
+
+    let thumbnail_path = thumbnail_path(&Storage::compute_hash(content))?;
+    if !thumbnail_path.exists() {
+        let image = image::load_from_memory(content)?;
+        let thumbnail = if image.width().max(image.height()) > THUMBNAIL_SIZE {
+            image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        } else {
+            image
+        };
+
+        // Write to a temporary file first so readers never see a partial image
+        let tmp_path = thumbnail_path.with_extension("tmp");
+        thumbnail.save_with_format(&tmp_path, ImageFormat::Png)?;
+        fs::rename(tmp_path, &thumbnail_path)?;
+    }
+    Ok(Some(thumbnail_path))
+}
+
+/// Thumbnail of an entry's image, created if missing, e.g. for entries
+/// captured before thumbnails existed. `None` for entries without an image.
+pub fn path_for(entry: &ClipboardEntry) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let Some((mime_type, _)) = entry.get_image_type() else {
+        return Ok(None);
+    };
+    if entry.is_sensitive() || crypto::current_key()?.is_some() {
+        return Ok(None);
+    }
+
+    let content = &entry.types[mime_type].content;
+    if let ContentRef::External(hash) = content {
+        let thumbnail_path = thumbnail_path(hash)?;
+        if thumbnail_path.exists() {
+            return Ok(Some(thumbnail_path));
+        }
+    }
+    create(&content.load()?)
+}
@@ -0,0 +1,218 @@
+use crate::r#impl::shell;
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::HashMap;
+
+pub const BUILTIN_TRANSFORMS: &[&str] = &[
+    "plain",
+    "trim",
+    "collapse-whitespace",
+    "upper",
+    "lower",
+    "json-pretty",
+    "json-minify",
+    "base64-encode",
+    "base64-decode",
+    "url-encode",
+    "url-decode",
+    "shell-quote",
+];
+
+/// A change applied to an entry's text before it is pasted
+pub enum Transform {
+    Builtin(&'static str),
+    /// Shell command reading the text on stdin and printing the result
+    Command(String),
+}
+
+/// Find a transform by name, preferring ones defined in `[transforms]`
+pub fn resolve(
+    name: &str,
+    transforms: &HashMap<String, String>,
+) -> Result<Transform, Box<dyn std::error::Error>> {
+    if let Some(command) = transforms.get(name) {
+        return Ok(Transform::Command(command.clone()));
+    }
+    BUILTIN_TRANSFORMS
+        .iter()
+        .find(|builtin| **builtin == name)
+        .map(|builtin| Transform::Builtin(builtin))
+        .ok_or_else(|| {
+            format!(
+                "Unknown transform '{}', expected one of {} or a transform from [transforms]",
+                name,
+                BUILTIN_TRANSFORMS.join(", ")
+            )
+            .into()
+        })
+}
+
+impl Transform {
+    pub fn apply(&self, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+        match self {
+            Transform::Builtin(name) => apply_builtin(name, text),
+            Transform::Command(command) => run_command(command, text),
+        }
+    }
+}
+
+fn apply_builtin(name: &str, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let transformed = match name {
+        // Text is plain already, rich types are dropped on paste
+        "plain" => text.to_string(),
+        "trim" => text.trim().to_string(),
+        "collapse-whitespace" => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        "upper" => text.to_uppercase(),
+        "lower" => text.to_lowercase(),
+        "json-pretty" => serde_json::to_string_pretty(&parse_json(text)?)?,
+        "json-minify" => serde_json::to_string(&parse_json(text)?)?,
+        "base64-encode" => general_purpose::STANDARD.encode(text),
+        "base64-decode" => {
+            // Line breaks from wrapped output are not part of the data
+            let encoded: String = text.split_whitespace().collect();
+            let decoded = general_purpose::STANDARD
+                .decode(&encoded)
+                .or_else(|_| general_purpose::URL_SAFE.decode(&encoded))
+                .map_err(|e| format!("Invalid base64: {}", e))?;
+            String::from_utf8(decoded).map_err(|_| "Decoded base64 is not UTF-8 text")?
+        }
+        "url-encode" => url_encode(text),
+        "url-decode" => url_decode(text)?,
+        "shell-quote" => shell_quote(text),
+        _ => return Err(format!("Unknown transform '{}'", name).into()),
+    };
+    Ok(transformed)
+}
+
+fn parse_json(text: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e).into())
+}
+
+/// Percent-encode everything but unreserved characters (RFC 3986)
+fn url_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decode percent escapes, and '+' as a space as in form data
+fn url_decode(text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = text
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| format!("Invalid percent escape at position {}", i))?;
+                decoded.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "Decoded URL is not UTF-8 text".into())
+}
+
+/// Quote text as a single POSIX shell word
+fn shell_quote(text: &str) -> String {
+    let safe = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if safe {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
+
+fn run_command(command: &str, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = shell::output(command, text.as_bytes().to_vec())
+        .map_err(|e| format!("Failed to run transform '{}': {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!("Transform '{}' failed with {}", command, output.status).into());
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| format!("Transform '{}' printed invalid UTF-8", command).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(name: &str, text: &str) -> String {
+        apply_builtin(name, text).unwrap()
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        let text = "héllo wörld\n";
+        assert_eq!(apply("base64-decode", &apply("base64-encode", text)), text);
+        // Wrapped output and the URL-safe alphabet decode too
+        assert_eq!(apply("base64-decode", "aMOp\nbGxv"), "héllo");
+        assert_eq!(apply("base64-decode", "Pz8-"), "??>");
+    }
+
+    #[test]
+    fn url_round_trips() {
+        let text = "a b&c=d/é?~";
+        let encoded = apply("url-encode", text);
+        assert_eq!(encoded, "a%20b%26c%3Dd%2F%C3%A9%3F~");
+        assert_eq!(apply("url-decode", &encoded), text);
+        assert_eq!(apply("url-decode", "a+b"), "a b");
+        assert!(apply_builtin("url-decode", "%zz").is_err());
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let minified = r#"{"b":[1,2],"a":{"c":null}}"#;
+        let pretty = apply("json-pretty", minified);
+        assert!(pretty.contains('\n'));
+        assert_eq!(apply("json-minify", &pretty), minified);
+        assert!(apply_builtin("json-pretty", "{").is_err());
+    }
+
+    #[test]
+    fn shell_quote_survives_the_shell() {
+        assert_eq!(shell_quote("plain-word.txt"), "plain-word.txt");
+        for text in ["it's", "a b", "$HOME `x` \"y\"", "", "\\n"] {
+            let quoted = shell_quote(text);
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("printf %s {}", quoted))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn commands_read_stdin() {
+        let transform = Transform::Command("tr a-z A-Z".to_string());
+        assert_eq!(transform.apply("abc").unwrap(), "ABC");
+        assert!(Transform::Command("exit 3".to_string()).apply("x").is_err());
+    }
+
+    #[test]
+    fn whitespace_transforms() {
+        assert_eq!(apply("trim", "  a b \n"), "a b");
+        assert_eq!(apply("collapse-whitespace", " a \n\t b "), "a b");
+    }
+}
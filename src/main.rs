@@ -2,6 +2,7 @@ mod commands;
 mod r#impl;
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "clippers")]
@@ -14,8 +15,206 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Watch,
+    List {
+        #[command(flatten)]
+        filter: commands::list::FilterArgs,
+        #[command(flatten)]
+        output: commands::list::OutputArgs,
+    },
+    /// List entries containing all words of a pattern (or words starting with them),
+    /// best match first
+    Search {
+        pattern: String,
+        /// Match the pattern as a regular expression instead, newest first
+        #[arg(long, conflicts_with = "substring")]
+        regex: bool,
+        /// Match the pattern as a literal substring instead, newest first
+        #[arg(long)]
+        substring: bool,
+        /// Case-sensitive matching for --regex and --substring
+        #[arg(long)]
+        case_sensitive: bool,
+        #[command(flatten)]
+        filter: commands::list::FilterArgs,
+        #[command(flatten)]
+        output: commands::list::OutputArgs,
+    },
+    /// Put the entry of a row read from stdin on the clipboard, or the joined
+    /// text of several rows, IDs or ranges
+    Pick {
+        /// Entry IDs or ranges such as 3-7, instead of rows from stdin
+        ids: Vec<String>,
+        /// Remove EXIF, XMP, ICC and comment metadata from images before pasting
+        #[arg(long)]
+        strip_metadata: bool,
+        /// Paste the entry's text changed by a transform: plain, trim, collapse-whitespace,
+        /// upper, lower, json-pretty, json-minify, base64-encode, base64-decode, url-encode,
+        /// url-decode, shell-quote or one from the config. Repeat to chain them.
+        #[arg(long = "transform", value_name = "NAME")]
+        transforms: Vec<String>,
+        #[command(flatten)]
+        join: commands::pick::JoinArgs,
+    },
+    /// Write an entry's content to stdout, or the joined text of several entries
+    Get {
+        /// Entry IDs, ranges such as 3-7, or rows printed by `list`
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// MIME type to output; images are converted to it if needed, e.g. image/jpeg
+        #[arg(long = "as")]
+        as_type: Option<String>,
+        #[command(flatten)]
+        join: commands::pick::JoinArgs,
+    },
+    /// Browse history in an interactive fuzzy finder and pick an entry
+    Menu {
+        /// Pick with an external launcher instead: rofi, wofi, fuzzel, dmenu, fzf,
+        /// choose, one from the config, or "tui" for the built-in menu
+        #[arg(long)]
+        launcher: Option<String>,
+    },
+    /// Backend for rofi's script mode: rofi -modi clippers:"clippers rofi" -show clippers
+    Rofi,
+    /// Print an entry for terminal previews, e.g. `fzf --preview 'clippers preview {}'`
+    Preview {
+        /// Entry ID, or a row printed by `list`
+        id: String,
+        /// Show this MIME type instead of the most readable one
+        #[arg(long = "type")]
+        mime_type: Option<String>,
+        /// Width in terminal cells [default: fzf preview or terminal width]
+        #[arg(long)]
+        width: Option<u16>,
+        /// Height in terminal cells [default: fzf preview or terminal height]
+        #[arg(long)]
+        height: Option<u16>,
+        /// How to draw images [default: detected from the terminal]
+        #[arg(long, value_enum)]
+        protocol: Option<r#impl::graphics::Protocol>,
+    },
+    /// Protect an entry from retention limits
+    Pin {
+        id: u64,
+    },
+    /// Remove the protection of a pinned entry
+    Unpin {
+        id: u64,
+    },
+    /// Add tags to an entry
+    Tag {
+        id: u64,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Set a short note on an entry, or remove it if no text is given
+    Note {
+        id: u64,
+        text: Option<String>,
+    },
+    /// Encrypt the clipboard history and blobs at rest
+    Encrypt {
+        /// Use a keyfile instead of a passphrase, generated if missing
+        #[arg(long)]
+        keyfile: Option<PathBuf>,
+    },
+    /// Decrypt an encrypted clipboard history
+    Decrypt,
+    /// Rebuild the full-text search index from history
+    Reindex,
+    /// Put the next older history entry on the clipboard, starting over from
+    /// the newest once something new is copied
+    Cycle {
+        /// Move to older entries (the default)
+        #[arg(long, conflicts_with = "forward")]
+        back: bool,
+        /// Move to newer entries instead
+        #[arg(long)]
+        forward: bool,
+    },
+    /// Paste entries in the order they were copied, e.g. to fill in forms
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommand,
+    },
+    /// Store the current clipboard, or piped stdin, in a register
+    Yank {
+        /// Register name, a letter or digit
+        #[arg(short, long)]
+        register: char,
+        /// MIME type of content from stdin, plain text by default
+        #[arg(long = "type", value_name = "MIME")]
+        mime_type: Option<String>,
+    },
+    /// Put the content of a register on the clipboard
+    Put {
+        /// Register name, a letter or digit
+        #[arg(short, long)]
+        register: char,
+    },
+    /// List registers with their content
+    Registers,
+    /// Named snippets kept apart from the history and offered in menus
+    Snippet {
+        #[command(subcommand)]
+        command: SnippetCommand,
+    },
+    /// Check the capture rules from the config
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Recompress all blobs in the blob store
+    Compact {
+        /// zstd compression level (1-22)
+        #[arg(long, default_value_t = r#impl::compression::DEFAULT_LEVEL, value_parser = clap::value_parser!(i32).range(1..=22))]
+        level: i32,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnippetCommand {
+    /// Save a snippet from stdin, a file or a history entry, replacing one of the same name
+    Add {
+        name: String,
+        /// Read the content from a file
+        #[arg(long, conflicts_with = "entry")]
+        file: Option<PathBuf>,
+        /// Copy all representations of a history entry
+        #[arg(long)]
+        entry: Option<u64>,
+        /// MIME type of content from stdin or a file, plain text by default
+        #[arg(long = "type", value_name = "MIME", conflicts_with = "entry")]
+        mime_type: Option<String>,
+    },
+    /// List snippets with their content
     List,
-    Pick,
+    /// Put a snippet on the clipboard
+    Paste { name: String },
+    /// Remove a snippet
+    Rm { name: String },
+}
+
+#[derive(Subcommand)]
+enum QueueCommand {
+    /// Queue entries copied from now on, each paste taking the next one
+    Start,
+    /// Show whether a queue is active and the entries left in it
+    Status,
+    /// Stop queueing, dropping entries not yet pasted
+    Stop,
+}
+
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// Show what the rules would do with content read from stdin, without storing it
+    Test {
+        /// MIME type of the content, plain text by default
+        #[arg(long = "type", value_name = "MIME")]
+        mime_type: Option<String>,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -25,11 +224,134 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Watch => {
             commands::watch::execute()?;
         }
-        Commands::List => {
-            commands::list::execute()?;
+        Commands::List { filter, output } => {
+            commands::list::execute(filter, output)?;
+        }
+        Commands::Search {
+            pattern,
+            regex,
+            substring,
+            case_sensitive,
+            filter,
+            output,
+        } => {
+            let mode = commands::search::SearchMode {
+                regex,
+                substring,
+                case_sensitive,
+            };
+            commands::search::execute(&pattern, mode, filter, output)?;
+        }
+        Commands::Reindex => {
+            commands::reindex::execute()?;
+        }
+        Commands::Cycle { back: _, forward } => {
+            commands::cycle::execute(forward)?;
+        }
+        Commands::Queue { command } => match command {
+            QueueCommand::Start => {
+                commands::queue::start()?;
+            }
+            QueueCommand::Status => {
+                commands::queue::status()?;
+            }
+            QueueCommand::Stop => {
+                commands::queue::stop()?;
+            }
+        },
+        Commands::Yank {
+            register,
+            mime_type,
+        } => {
+            commands::register::yank(register, mime_type)?;
+        }
+        Commands::Put { register } => {
+            commands::register::put(register)?;
+        }
+        Commands::Registers => {
+            commands::register::list()?;
+        }
+        Commands::Snippet { command } => match command {
+            SnippetCommand::Add {
+                name,
+                file,
+                entry,
+                mime_type,
+            } => {
+                let source = match (file, entry) {
+                    (Some(file), _) => commands::snippet::SnippetSource::File(file),
+                    (_, Some(id)) => commands::snippet::SnippetSource::Entry(id),
+                    _ => commands::snippet::SnippetSource::Stdin,
+                };
+                commands::snippet::add(&name, source, mime_type)?;
+            }
+            SnippetCommand::List => {
+                commands::snippet::list()?;
+            }
+            SnippetCommand::Paste { name } => {
+                commands::snippet::paste(&name)?;
+            }
+            SnippetCommand::Rm { name } => {
+                commands::snippet::remove(&name)?;
+            }
+        },
+        Commands::Rules { command } => match command {
+            RulesCommand::Test { mime_type } => {
+                commands::rules::test(mime_type)?;
+            }
+        },
+        Commands::Pick {
+            ids,
+            strip_metadata,
+            transforms,
+            join,
+        } => {
+            commands::pick::execute(&ids, strip_metadata, &transforms, &join)?;
+        }
+        Commands::Get { ids, as_type, join } => {
+            commands::get::execute(&ids, as_type, &join)?;
+        }
+        Commands::Menu { launcher } => {
+            commands::menu::execute(launcher)?;
+        }
+        Commands::Rofi => {
+            commands::rofi::execute()?;
+        }
+        Commands::Preview {
+            id,
+            mime_type,
+            width,
+            height,
+            protocol,
+        } => {
+            let args = commands::preview::PreviewArgs {
+                mime_type,
+                width,
+                height,
+                protocol,
+            };
+            commands::preview::execute(&id, args)?;
+        }
+        Commands::Pin { id } => {
+            commands::pin::execute(id, true)?;
+        }
+        Commands::Unpin { id } => {
+            commands::pin::execute(id, false)?;
+        }
+        Commands::Tag { id, tags, remove } => {
+            commands::tag::execute(id, &tags, remove)?;
+        }
+        Commands::Note { id, text } => {
+            commands::note::execute(id, text)?;
+        }
+        Commands::Encrypt { keyfile } => {
+            commands::encrypt::execute(keyfile)?;
+        }
+        Commands::Decrypt => {
+            commands::decrypt::execute()?;
         }
-        Commands::Pick => {
-            commands::pick::execute()?;
+        Commands::Compact { level } => {
+            commands::compact::execute(level)?;
         }
     }
 